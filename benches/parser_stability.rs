@@ -153,7 +153,7 @@ fn run_benchmark() {
         let chunk_size = generator.chunk_size();
 
         // Process the chunk
-        let result = parser.process_chunk(&chunk).expect("Parsing failed");
+        let result = parser.process_chunk(chunk).expect("Parsing failed");
         let rows_in_chunk = result.complete_rows.len();
 
         // Validate that chunking functionality works correctly
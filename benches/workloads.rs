@@ -0,0 +1,160 @@
+// benches/workloads.rs
+//
+// Criterion benchmarks, one group per workload shape, so that a change to the
+// state machine shows where it helps or hurts instead of averaging everything
+// into one synthetic row.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rust_csv_parser::{count_records, CsvChunkParser, CsvConfig, CsvReader};
+use std::hint::black_box;
+
+// --- Configuration Constants ---
+const TARGET_INPUT_BYTES: usize = 256 * 1024; // Each workload is ~256KB of CSV
+
+// --- Workload Generators ---
+// Each generator repeats its row until the input reaches TARGET_INPUT_BYTES.
+fn repeat_rows(row: impl Fn(usize) -> String) -> String {
+    let mut input = String::with_capacity(TARGET_INPUT_BYTES + 4096);
+    let mut i = 0;
+    while input.len() < TARGET_INPUT_BYTES {
+        input.push_str(&row(i));
+        input.push('\n');
+        i += 1;
+    }
+    input
+}
+
+// Four short unquoted fields per row
+fn narrow_rows() -> String {
+    repeat_rows(|i| format!("{},GB,{}.99,ok", i, i % 100))
+}
+
+// 200 short unquoted fields per row
+fn wide_rows() -> String {
+    repeat_rows(|i| (0..200).map(|col| format!("v{}", (i + col) % 1000)).collect::<Vec<_>>().join(","))
+}
+
+// Every field quoted, with doubled quotes, delimiters and line breaks inside
+fn heavy_quoting() -> String {
+    repeat_rows(|i| {
+        format!(
+            "\"{}\",\"Smith, \"\"Dr\"\" J\",\"123 Main St\nFlat {}\",\"note, with \"\"quotes\"\"\",\"{}.50\"",
+            i,
+            i % 40,
+            i % 1000
+        )
+    })
+}
+
+// A few fields of several KB each, half of them quoted
+fn long_fields() -> String {
+    let text = "lorem ipsum dolor sit amet ".repeat(150);
+    repeat_rows(|i| format!("{},{},\"{}\",end", i, text, text))
+}
+
+// Multi-byte text in every field
+fn unicode_dense() -> String {
+    repeat_rows(|i| format!("{},Zürich,東京都渋谷区,Ελληνικά κείμενο,🚀🎉✨,\"Москва, Россия\"", i))
+}
+
+// Feeds `input` to one parser in `chunk_size`-byte pieces (rounded to char
+// boundaries), carrying leftovers forward the way a streaming reader does.
+fn parse_in_chunks(input: &str, chunk_size: usize) -> usize {
+    parse_chunks(input, chunk_size, true)
+}
+
+// `parse_in_chunks`, optionally dropping each chunk's rows instead of handing
+// their buffers back to the parser
+fn parse_chunks(input: &str, chunk_size: usize, recycle: bool) -> usize {
+    let mut parser = CsvChunkParser::new(CsvConfig::default());
+    let mut rows = 0;
+    let mut start = 0;
+    while start < input.len() {
+        let mut end = (start + chunk_size).min(input.len());
+        while !input.is_char_boundary(end) {
+            end += 1;
+        }
+        let result = parser.process_chunk(&input[start..end]).expect("Parsing failed");
+        rows += result.complete_rows.len();
+        if recycle {
+            parser.recycle_rows(result.complete_rows);
+        }
+        start = end;
+    }
+    // An empty chunk marks the end of input and flushes the last record
+    rows + parser.process_chunk("").expect("Parsing failed").complete_rows.len()
+}
+
+// --- Benchmarks ---
+fn bench_workloads(c: &mut Criterion) {
+    let workloads = [
+        ("narrow_rows", narrow_rows()),
+        ("wide_rows", wide_rows()),
+        ("heavy_quoting", heavy_quoting()),
+        ("long_fields", long_fields()),
+        ("unicode_dense", unicode_dense()),
+    ];
+
+    let mut group = c.benchmark_group("workloads");
+    for (name, input) in &workloads {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), input, |b, input| {
+            b.iter(|| parse_in_chunks(black_box(input), 64 * 1024))
+        });
+    }
+    group.finish();
+}
+
+fn bench_tiny_chunks(c: &mut Criterion) {
+    // The same input at shrinking chunk sizes, to show the per-chunk overhead
+    // of carrying partial records across boundaries
+    let input = heavy_quoting();
+
+    let mut group = c.benchmark_group("tiny_chunk_streaming");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    for chunk_size in [16, 64, 256, 4096] {
+        group.bench_with_input(BenchmarkId::from_parameter(chunk_size), &chunk_size, |b, &chunk_size| {
+            b.iter(|| parse_in_chunks(black_box(&input), chunk_size))
+        });
+    }
+    group.finish();
+}
+
+fn bench_count_only(c: &mut Criterion) {
+    // Reading every record against counting them without building fields
+    let input = wide_rows();
+
+    let mut group = c.benchmark_group("count_only");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("read_records", |b| {
+        b.iter(|| {
+            let mut reader = CsvReader::new(black_box(input.as_bytes()), CsvConfig::default());
+            let mut rows = 0;
+            while reader.next_record().expect("Parsing failed").is_some() {
+                rows += 1;
+            }
+            rows
+        })
+    });
+    group.bench_function("count_records", |b| {
+        b.iter(|| count_records(black_box(input.as_bytes()), CsvConfig::default()).expect("Parsing failed"))
+    });
+    group.finish();
+}
+
+fn bench_row_recycling(c: &mut Criterion) {
+    // Dropping every chunk's rows against recycling their buffers, on short
+    // rows where allocation is a large share of the work
+    let input = narrow_rows();
+
+    let mut group = c.benchmark_group("row_recycling");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    for (name, recycle) in [("fresh_rows", false), ("recycled_rows", true)] {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &recycle, |b, &recycle| {
+            b.iter(|| parse_chunks(black_box(&input), 4096, recycle))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_workloads, bench_tiny_chunks, bench_count_only, bench_row_recycling);
+criterion_main!(benches);
@@ -0,0 +1,116 @@
+//! Date and date-time parsing for `chrono` columns.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use std::collections::HashMap;
+
+/// Date layouts tried, in order, when a column has no explicit format.
+/// Slash-separated dates are read day-first.
+pub const COMMON_DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%d/%m/%Y", "%d.%m.%Y", "%d-%b-%Y", "%Y%m%d"];
+
+/// Date-time layouts tried, in order, after RFC 3339 when a column has no
+/// explicit format. Times without an offset are taken as UTC.
+pub const COMMON_DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%d/%m/%Y %H:%M:%S",
+    "%d/%m/%Y %H:%M",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ColumnKind {
+    Date(Option<String>),
+    DateTime(Option<String>),
+}
+
+/// Per-column date/time formats for `StringRecord::deserialize_with`.
+/// Columns are matched by header name; empty fields are left untouched so
+/// `Option<NaiveDate>` still deserializes to `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DateFormats {
+    columns: HashMap<String, ColumnKind>,
+}
+
+impl DateFormats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses `column` as a `NaiveDate` using a chrono format string.
+    pub fn date(mut self, column: &str, format: &str) -> Self {
+        self.columns.insert(column.to_string(), ColumnKind::Date(Some(format.to_string())));
+        self
+    }
+
+    /// Parses `column` as a `NaiveDate`, detecting the layout from `COMMON_DATE_FORMATS`.
+    pub fn auto_date(mut self, column: &str) -> Self {
+        self.columns.insert(column.to_string(), ColumnKind::Date(None));
+        self
+    }
+
+    /// Parses `column` as a `DateTime<Utc>` using a chrono format string. Formats
+    /// with an offset (`%z`) are converted to UTC; formats without one are taken as UTC.
+    pub fn datetime(mut self, column: &str, format: &str) -> Self {
+        self.columns.insert(column.to_string(), ColumnKind::DateTime(Some(format.to_string())));
+        self
+    }
+
+    /// Parses `column` as a `DateTime<Utc>`, trying RFC 3339, then
+    /// `COMMON_DATETIME_FORMATS`, then a bare date at midnight.
+    pub fn auto_datetime(mut self, column: &str) -> Self {
+        self.columns.insert(column.to_string(), ColumnKind::DateTime(None));
+        self
+    }
+
+    /// The canonical text for `value` when `column` is a date/time column:
+    /// `%Y-%m-%d` for dates and RFC 3339 for date-times.
+    pub(crate) fn normalize(&self, column: &str, value: &str) -> Option<Result<String, String>> {
+        let kind = self.columns.get(column)?;
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+        Some(match kind {
+            ColumnKind::Date(format) => parse_date_with(value, format.as_deref())
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .ok_or_else(|| format!("cannot parse {:?} as a date", value)),
+            ColumnKind::DateTime(format) => parse_datetime_with(value, format.as_deref())
+                .map(|datetime| datetime.to_rfc3339())
+                .ok_or_else(|| format!("cannot parse {:?} as a date-time", value)),
+        })
+    }
+}
+
+/// Parses a date in any of `COMMON_DATE_FORMATS`.
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    parse_date_with(value.trim(), None)
+}
+
+/// Parses a date-time as `DateFormats::auto_datetime` does.
+pub fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    parse_datetime_with(value.trim(), None)
+}
+
+fn parse_date_with(value: &str, format: Option<&str>) -> Option<NaiveDate> {
+    match format {
+        Some(format) => NaiveDate::parse_from_str(value, format).ok(),
+        None => COMMON_DATE_FORMATS.iter().find_map(|format| NaiveDate::parse_from_str(value, format).ok()),
+    }
+}
+
+fn parse_datetime_with(value: &str, format: Option<&str>) -> Option<DateTime<Utc>> {
+    let with_format = |format: &str| {
+        DateTime::parse_from_str(value, format)
+            .map(|datetime| datetime.with_timezone(&Utc))
+            .or_else(|_| NaiveDateTime::parse_from_str(value, format).map(|naive| naive.and_utc()))
+            .ok()
+    };
+    match format {
+        Some(format) => with_format(format),
+        None => DateTime::parse_from_rfc3339(value)
+            .map(|datetime| datetime.with_timezone(&Utc))
+            .ok()
+            .or_else(|| COMMON_DATETIME_FORMATS.iter().find_map(|format| with_format(format)))
+            .or_else(|| parse_date_with(value, None).and_then(|date| date.and_hms_opt(0, 0, 0)).map(|naive| naive.and_utc())),
+    }
+}
@@ -0,0 +1,786 @@
+//! Serde deserialization of records into typed values.
+
+use super::*;
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::borrow::Cow;
+
+impl StringRecord {
+    /// Deserializes this record into `T`. Structs are matched by header name when
+    /// the stream has headers, and by position otherwise. `&str` fields borrow
+    /// directly from the record instead of allocating.
+    pub fn deserialize<'de, T: serde::Deserialize<'de>>(&'de self) -> Result<T, CsvError> {
+        let mut deserializer = RecordDeserializer::new(self);
+        T::deserialize(&mut deserializer).map_err(|error| CsvError::Deserialize {
+            record_index: self.record_index,
+            error,
+        })
+    }
+
+    /// Like `deserialize`, with lenient enum matching and (with the `chrono`
+    /// feature) date/time column formats taken from `options`.
+    pub fn deserialize_with<'de, T: serde::Deserialize<'de>>(&'de self, options: &DeserializeOptions) -> Result<T, CsvError> {
+        let mut deserializer = RecordDeserializer::new(self).with_options(options);
+        T::deserialize(&mut deserializer).map_err(|error| CsvError::Deserialize {
+            record_index: self.record_index,
+            error,
+        })
+    }
+}
+
+impl<R: std::io::Read> CsvReader<R> {
+    /// Reads every remaining record into `T`, stopping at the first error.
+    pub fn deserialize_all<T: serde::de::DeserializeOwned>(self) -> Result<Vec<T>, CsvError> {
+        self.map(|record| record?.deserialize()).collect()
+    }
+
+    /// Like `deserialize_all`, but deserializes the decoded records across the
+    /// rayon thread pool. Parsing itself stays sequential; the whole input is
+    /// decoded before any record is deserialized. Output order matches input order.
+    #[cfg(feature = "parallel")]
+    pub fn par_deserialize_all<T>(self) -> Result<Vec<T>, CsvError>
+    where
+        T: serde::de::DeserializeOwned + Send,
+    {
+        use rayon::prelude::*;
+
+        let records = self.collect::<Result<Vec<_>, _>>()?;
+        records.par_iter().map(StringRecord::deserialize).collect()
+    }
+}
+
+/// Why a record could not be deserialized, and which field (if known) was at fault.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeserializeError {
+    pub field: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.field {
+            Some(field) => write!(f, "field {}: {}", field, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+impl de::Error for DeserializeError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        DeserializeError { field: None, message: msg.to_string() }
+    }
+}
+
+/// Cleanup callback for one column: receives the field text and returns the
+/// text to deserialize, or a message explaining why the value is rejected.
+pub type ColumnMapFn = std::sync::Arc<dyn Fn(&str) -> Result<String, String> + Send + Sync>;
+
+/// Lenient-matching settings for `StringRecord::deserialize_with`.
+#[derive(Clone, Default)]
+pub struct DeserializeOptions {
+    case_insensitive_enums: bool,
+    /// (alias, variant) pairs.
+    enum_aliases: Vec<(String, String)>,
+    column_maps: std::collections::HashMap<String, ColumnMapFn>,
+    number_format: Option<NumberFormat>,
+    column_number_formats: std::collections::HashMap<String, NumberFormat>,
+    #[cfg(feature = "chrono")]
+    date_formats: DateFormats,
+}
+
+impl std::fmt::Debug for DeserializeOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("DeserializeOptions");
+        debug
+            .field("case_insensitive_enums", &self.case_insensitive_enums)
+            .field("enum_aliases", &self.enum_aliases)
+            .field("mapped_columns", &self.column_maps.keys().collect::<Vec<_>>())
+            .field("number_format", &self.number_format)
+            .field("column_number_formats", &self.column_number_formats);
+        #[cfg(feature = "chrono")]
+        debug.field("date_formats", &self.date_formats);
+        debug.finish()
+    }
+}
+
+impl DeserializeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match unit enum variants ignoring ASCII case, so `SALE`, `Sale` and
+    /// `sale` all select `Sale`. Also applies to aliases.
+    pub fn case_insensitive_enums(mut self, yes: bool) -> Self {
+        self.case_insensitive_enums = yes;
+        self
+    }
+
+    /// Accept each of `aliases` as the enum variant named `variant`, for any
+    /// enum that has such a variant (e.g. `"S"` and `"Sold"` for `Sale`).
+    pub fn enum_aliases(mut self, variant: &str, aliases: &[&str]) -> Self {
+        self.enum_aliases.extend(aliases.iter().map(|alias| (alias.to_string(), variant.to_string())));
+        self
+    }
+
+    /// Run `map` over every field of the column named `column` before it is
+    /// deserialized, e.g. to strip thousands separators or turn `YES`/`NO`
+    /// into `true`/`false`. Null tokens are checked against the mapped text.
+    pub fn map_column<F>(mut self, column: &str, map: F) -> Self
+    where
+        F: Fn(&str) -> Result<String, String> + Send + Sync + 'static,
+    {
+        self.column_maps.insert(column.to_string(), std::sync::Arc::new(map));
+        self
+    }
+
+    /// Read numeric fields (integers and floats) written with `format`'s
+    /// separators, in every column without a format of its own.
+    pub fn number_format(mut self, format: NumberFormat) -> Self {
+        self.number_format = Some(format);
+        self
+    }
+
+    /// Read numeric fields of the column named `column` with `format`.
+    pub fn column_number_format(mut self, column: &str, format: NumberFormat) -> Self {
+        self.column_number_formats.insert(column.to_string(), format);
+        self
+    }
+
+    /// Convert the date/time columns named in `formats` into the text
+    /// chrono's own `Deserialize` impls expect, so `NaiveDate` and
+    /// `DateTime<Utc>` fields work with any source format.
+    #[cfg(feature = "chrono")]
+    pub fn date_formats(mut self, formats: DateFormats) -> Self {
+        self.date_formats = formats;
+        self
+    }
+
+    /// The declared variant that `text` selects, if it only matches leniently.
+    fn resolve_variant(&self, text: &str, variants: &'static [&'static str]) -> Option<&'static str> {
+        let matches = |a: &str, b: &str| if self.case_insensitive_enums { a.eq_ignore_ascii_case(b) } else { a == b };
+        if variants.contains(&text) {
+            return None;
+        }
+        let target = self
+            .enum_aliases
+            .iter()
+            .find(|(alias, _)| matches(alias, text))
+            .map_or(text, |(_, variant)| variant.as_str());
+        variants.iter().copied().find(|variant| matches(variant, target))
+    }
+}
+
+/// One map entry: a named column, or a nested struct field and the
+/// prefix-stripped columns it covers.
+enum MapEntry<'de> {
+    Column(&'de str, usize),
+    Nested(&'static str, Vec<(&'de str, usize)>),
+}
+
+/// Walks the fields of one record, as a map keyed by header or as a sequence.
+pub(crate) struct RecordDeserializer<'de, 'f> {
+    record: &'de StringRecord,
+    headers: Option<&'de [String]>,
+    options: Option<&'f DeserializeOptions>,
+    /// The (key, column) pairs of a nested struct; `None` for the whole record.
+    scope: Option<Vec<(&'de str, usize)>>,
+    pos: usize,
+    entries: std::collections::VecDeque<MapEntry<'de>>,
+    value: Option<MapEntry<'de>>,
+}
+
+impl<'de, 'f> RecordDeserializer<'de, 'f> {
+    pub(crate) fn new(record: &'de StringRecord) -> Self {
+        RecordDeserializer {
+            record,
+            headers: record.headers(),
+            options: None,
+            scope: None,
+            pos: 0,
+            entries: std::collections::VecDeque::new(),
+            value: None,
+        }
+    }
+
+    pub(crate) fn with_options(mut self, options: &'f DeserializeOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    fn nested(&self, columns: Vec<(&'de str, usize)>) -> Self {
+        RecordDeserializer { options: self.options, scope: Some(columns), ..RecordDeserializer::new(self.record) }
+    }
+
+    /// Queues the map entries for a struct with `fields` (or, when empty, for
+    /// a plain map). A field with no column of its own takes every column
+    /// named `<field>_<key>` as a nested struct keyed by `<key>`, so
+    /// `buyer: Buyer { name, postcode }` reads `buyer_name` and `buyer_postcode`.
+    fn plan_entries(&mut self, fields: &'static [&'static str]) {
+        let mut columns: Vec<(&'de str, usize)> = match &self.scope {
+            Some(columns) => columns.clone(),
+            None => self.headers.unwrap_or_default().iter().map(String::as_str).zip(0..self.record.len()).collect(),
+        };
+        for &field in fields {
+            if columns.iter().any(|&(key, _)| key == field) {
+                continue;
+            }
+            let prefix = format!("{}_", field);
+            let mut nested = Vec::new();
+            columns.retain(|&(key, i)| match key.strip_prefix(prefix.as_str()) {
+                Some(rest) if !fields.contains(&key) => {
+                    nested.push((rest, i));
+                    false
+                }
+                _ => true,
+            });
+            if !nested.is_empty() {
+                self.entries.push_back(MapEntry::Nested(field, nested));
+            }
+        }
+        self.entries.extend(columns.into_iter().map(|(key, i)| MapEntry::Column(key, i)));
+    }
+
+    fn remaining(&self) -> usize {
+        match &self.scope {
+            Some(columns) => columns.len().saturating_sub(self.pos),
+            None => self.record.len().saturating_sub(self.pos),
+        }
+    }
+
+    fn next_field(&mut self) -> Result<FieldDeserializer<'de, 'f>, DeserializeError> {
+        let index = match &self.scope {
+            Some(columns) => columns.get(self.pos).map(|&(_, i)| i),
+            None => Some(self.pos),
+        };
+        self.pos += 1;
+        match index {
+            Some(index) => self.field_at(index),
+            None => Err(DeserializeError { field: None, message: "nested struct has too few columns".to_string() }),
+        }
+    }
+
+    fn field_at(&self, index: usize) -> Result<FieldDeserializer<'de, 'f>, DeserializeError> {
+        let field = self.record.get(index).ok_or_else(|| DeserializeError {
+            field: Some(index),
+            message: "record has too few fields".to_string(),
+        })?;
+        let field = self.clean(index, field)?;
+        let is_null = self.record.nulls.contains(index) || self.record.null_tokens.contains(&&*field);
+        let number_format = self.options.and_then(|options| {
+            let name = self.headers.and_then(|headers| headers.get(index));
+            name.and_then(|name| options.column_number_formats.get(name)).or(options.number_format.as_ref()).copied()
+        });
+        Ok(FieldDeserializer { field, index, is_null, options: self.options, number_format })
+    }
+
+    /// Applies the column's `map_column` callback and date format, if any.
+    fn clean(&self, index: usize, field: &'de str) -> Result<Cow<'de, str>, DeserializeError> {
+        let mut field = Cow::Borrowed(field);
+        let (Some(options), Some(name)) = (self.options, self.headers.and_then(|h| h.get(index))) else {
+            return Ok(field);
+        };
+        let error = |message| DeserializeError { field: Some(index), message };
+        if let Some(map) = options.column_maps.get(name) {
+            field = Cow::Owned(map(&field).map_err(error)?);
+        }
+        #[cfg(feature = "chrono")]
+        if let Some(normalized) = options.date_formats.normalize(name, &field).transpose().map_err(error)? {
+            field = Cow::Owned(normalized);
+        }
+        Ok(field)
+    }
+}
+
+/// Scalar targets (e.g. a bare `String` or `i64`) take the record's next field.
+macro_rules! forward_to_next_field {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.next_field()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for &mut RecordDeserializer<'de, '_> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_struct("", &[], visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_struct("", &[], visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        if self.headers.is_some() {
+            self.plan_entries(fields);
+            visitor.visit_map(self)
+        } else {
+            visitor.visit_seq(self)
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(self)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(self)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(self)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.next_field()?.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_next_field! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_unit deserialize_identifier
+    }
+}
+
+impl<'de> SeqAccess<'de> for &mut RecordDeserializer<'de, '_> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        if self.remaining() == 0 {
+            return Ok(None);
+        }
+        let field = self.next_field()?;
+        seed.deserialize(field).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining())
+    }
+}
+
+impl<'de> MapAccess<'de> for &mut RecordDeserializer<'de, '_> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        let Some(entry) = self.entries.pop_front() else {
+            return Ok(None);
+        };
+        let key = match entry {
+            MapEntry::Column(key, _) => key,
+            MapEntry::Nested(key, _) => key,
+        };
+        self.value = Some(entry);
+        seed.deserialize(de::value::BorrowedStrDeserializer::new(key)).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        match self.value.take() {
+            Some(MapEntry::Column(_, index)) => seed.deserialize(self.field_at(index)?),
+            Some(MapEntry::Nested(_, columns)) => seed.deserialize(&mut self.nested(columns)),
+            None => Err(de::Error::custom("map value requested before its key")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// Deserializes a single field, parsing numbers and booleans from its text.
+pub(crate) struct FieldDeserializer<'de, 'f> {
+    /// Borrowed from the record unless a column callback or date format rewrote it.
+    field: Cow<'de, str>,
+    index: usize,
+    is_null: bool,
+    options: Option<&'f DeserializeOptions>,
+    /// Separators of numeric text, if the column is locale-formatted.
+    number_format: Option<NumberFormat>,
+}
+
+impl FieldDeserializer<'_, '_> {
+    /// The field as numeric text, in canonical form if the column has a `NumberFormat`.
+    fn numeric_text(&self) -> Cow<'_, str> {
+        match self.number_format.and_then(|format| format.normalize(&self.field)) {
+            Some(normalized) => Cow::Owned(normalized),
+            None => Cow::Borrowed(&self.field),
+        }
+    }
+
+    fn error(&self, message: String) -> DeserializeError {
+        DeserializeError { field: Some(self.index), message }
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident: $ty:ty,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.numeric_text().parse::<$ty>() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => Err(self.error(format!("cannot parse {:?} as {}", self.field, stringify!($ty)))),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for FieldDeserializer<'de, '_> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.field {
+            Cow::Borrowed(text) => visitor.visit_borrowed_str(text),
+            Cow::Owned(text) => visitor.visit_string(text),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool: bool,
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_i128 => visit_i128: i128,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_u128 => visit_u128: u128,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+        deserialize_char => visit_char: char,
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.field {
+            Cow::Borrowed(text) => visitor.visit_borrowed_str(text),
+            Cow::Owned(text) => visitor.visit_string(text),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.field {
+            Cow::Borrowed(text) => visitor.visit_borrowed_str(text),
+            Cow::Owned(text) => visitor.visit_string(text),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.field {
+            Cow::Borrowed(text) => visitor.visit_borrowed_bytes(text.as_bytes()),
+            Cow::Owned(text) => visitor.visit_byte_buf(text.into_bytes()),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    /// Null tokens (by default just the empty field) are `None`.
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.is_null {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Unit variants are matched by name, or leniently per `DeserializeOptions`.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let index = self.index;
+        let variant = self
+            .options
+            .and_then(|options| options.resolve_variant(&self.field, variants))
+            .unwrap_or(&self.field);
+        visitor
+            .visit_enum(de::value::StrDeserializer::<DeserializeError>::new(variant))
+            .map_err(|e| DeserializeError { field: Some(index), ..e })
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.field {
+            Cow::Borrowed(text) => visitor.visit_borrowed_str(text),
+            Cow::Owned(text) => visitor.visit_string(text),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        seq tuple tuple_struct map struct
+    }
+}
+
+/// The rest of the stream as a sequence of records, for transcoding with
+/// `serde_transcode` or deserializing a `Vec<T>` in one call. Records are
+/// parsed one chunk at a time and dropped once visited; their text is
+/// copied out rather than borrowed.
+impl<'de, R: std::io::Read> Deserializer<'de> for &mut CsvReader<R> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(StreamAccess { reader: self })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct StreamAccess<'a, R: std::io::Read> {
+    reader: &'a mut CsvReader<R>,
+}
+
+impl<'de, R: std::io::Read> SeqAccess<'de> for StreamAccess<'_, R> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        let record = self.reader.next_record().map_err(|e| DeserializeError { field: None, message: format!("{:?}", e) })?;
+        match record {
+            Some(record) => seed.deserialize(OwnedRecord { record: &record, pos: 0 }).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A record whose fields are copied into the visitor, so it can stand in
+/// for any `'de`. Headered records are maps, headerless ones sequences.
+struct OwnedRecord<'a> {
+    record: &'a StringRecord,
+    pos: usize,
+}
+
+impl OwnedRecord<'_> {
+    fn next_field(&mut self) -> InferredField<'_> {
+        let index = self.pos;
+        self.pos += 1;
+        InferredField { field: self.record.get(index).unwrap_or_default(), index, is_null: self.record.is_null(index) }
+    }
+
+    fn len(&self) -> usize {
+        match self.record.headers() {
+            Some(names) => names.len().min(self.record.len()),
+            None => self.record.len(),
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for OwnedRecord<'_> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.record.headers().is_some() {
+            visitor.visit_map(&mut self)
+        } else {
+            visitor.visit_seq(&mut self)
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> SeqAccess<'de> for &mut OwnedRecord<'_> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        if self.pos >= self.len() {
+            return Ok(None);
+        }
+        seed.deserialize(self.next_field()).map(Some)
+    }
+}
+
+impl<'de> MapAccess<'de> for &mut OwnedRecord<'_> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        if self.pos >= self.len() {
+            return Ok(None);
+        }
+        let name = &self.record.headers().unwrap_or_default()[self.pos];
+        seed.deserialize(de::value::StrDeserializer::new(name)).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        seed.deserialize(self.next_field())
+    }
+}
+
+/// A copied-out field. Self-describing targets (`deserialize_any`) get null
+/// tokens as `None` and booleans and numbers as such; a number with a leading
+/// zero or `+` (a code like `007`) stays text.
+struct InferredField<'a> {
+    field: &'a str,
+    index: usize,
+    is_null: bool,
+}
+
+impl InferredField<'_> {
+    fn numeric_text(&self) -> &str {
+        self.field
+    }
+
+    fn error(&self, message: String) -> DeserializeError {
+        DeserializeError { field: Some(self.index), message }
+    }
+
+    fn looks_numeric(&self) -> bool {
+        let digits = self.field.strip_prefix('-').unwrap_or(self.field);
+        let keeps_zeros = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+        digits.starts_with(|c: char| c.is_ascii_digit()) && !keeps_zeros
+    }
+}
+
+impl<'de> Deserializer<'de> for InferredField<'_> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.is_null {
+            return visitor.visit_none();
+        }
+        match self.field {
+            "true" => return visitor.visit_bool(true),
+            "false" => return visitor.visit_bool(false),
+            _ => {}
+        }
+        if self.looks_numeric() {
+            if let Ok(value) = self.field.parse::<i64>() {
+                return visitor.visit_i64(value);
+            }
+            if let Ok(value) = self.field.parse::<u64>() {
+                return visitor.visit_u64(value);
+            }
+            if let Ok(value) = self.field.parse::<f64>() {
+                return visitor.visit_f64(value);
+            }
+        }
+        visitor.visit_str(self.field)
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool: bool,
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_i128 => visit_i128: i128,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_u128 => visit_u128: u128,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+        deserialize_char => visit_char: char,
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.field)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.field)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.field)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.is_null {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(de::value::StrDeserializer::<DeserializeError>::new(self.field))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bytes byte_buf unit unit_struct newtype_struct seq tuple tuple_struct map struct ignored_any
+    }
+}
@@ -0,0 +1,344 @@
+//! Random access by record number: `Indexer`, `CsvIndex` and `IndexedReader`.
+
+use super::*;
+
+/// Records between the entries of a `CsvIndex` unless `Indexer::with_stride` says otherwise.
+const DEFAULT_INDEX_STRIDE: usize = 1024;
+
+/// Leading bytes of a serialized `CsvIndex`, then its format version.
+const INDEX_MAGIC: &[u8; 4] = b"CSVI";
+const INDEX_VERSION: u8 = 1;
+
+/// Where one indexed record starts; its record index is implied by its slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IndexEntry {
+    byte_offset: usize,
+    line: usize,
+    column: usize,
+}
+
+/// The start of every `stride`th record of a file, with its header row, as
+/// built by `Indexer`. Offsets come from parsing, so quoted line breaks
+/// never count as record boundaries. Only valid for the file and
+/// `CsvConfig` it was built with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvIndex {
+    stride: usize,
+    records: usize,
+    headers: Option<Vec<String>>,
+    /// The field count `strict_field_count` holds records to.
+    expected_fields: Option<usize>,
+    entries: Vec<IndexEntry>,
+}
+
+impl CsvIndex {
+    /// Records between indexed ones.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Data records in the file.
+    pub fn len(&self) -> usize {
+        self.records
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records == 0
+    }
+
+    /// The header row, if the file was indexed with `has_headers`.
+    pub fn headers(&self) -> Option<&[String]> {
+        self.headers.as_deref()
+    }
+
+    /// Where record `k` starts, if it is an indexed one.
+    pub fn position(&self, k: usize) -> Option<Position> {
+        if !k.is_multiple_of(self.stride) || k >= self.records {
+            return None;
+        }
+        self.entry_before(k)
+    }
+
+    /// The indexed record at or before record `k`.
+    fn entry_before(&self, k: usize) -> Option<Position> {
+        let entry = self.entries.get(k / self.stride)?;
+        Some(Position {
+            line: entry.line,
+            column: entry.column,
+            byte_offset: entry.byte_offset,
+            record_index: k / self.stride * self.stride,
+        })
+    }
+
+    /// Writes the index in a compact binary form: offsets and line numbers
+    /// are stored as varint deltas from the entry before, so an entry is
+    /// usually a few bytes.
+    pub fn write_to<W: std::io::Write>(&self, mut out: W) -> Result<(), CsvError> {
+        let mut bytes = Vec::with_capacity(16 + 4 * self.entries.len());
+        bytes.extend_from_slice(INDEX_MAGIC);
+        bytes.push(INDEX_VERSION);
+        write_varint(&mut bytes, self.stride);
+        write_varint(&mut bytes, self.records);
+        write_varint(&mut bytes, self.expected_fields.map_or(0, |fields| fields + 1));
+        write_varint(&mut bytes, self.headers.as_ref().map_or(0, |headers| headers.len() + 1));
+        for name in self.headers.iter().flatten() {
+            write_varint(&mut bytes, name.len());
+            bytes.extend_from_slice(name.as_bytes());
+        }
+        write_varint(&mut bytes, self.entries.len());
+        let (mut byte_offset, mut line) = (0, 0);
+        for entry in &self.entries {
+            write_varint(&mut bytes, entry.byte_offset - byte_offset);
+            write_varint(&mut bytes, entry.line - line);
+            write_varint(&mut bytes, entry.column);
+            (byte_offset, line) = (entry.byte_offset, entry.line);
+        }
+        out.write_all(&bytes)?;
+        Ok(())
+    }
+
+    /// Reads an index written by `write_to`. Anything else fails with
+    /// `CsvError::InvalidIndex`.
+    pub fn read_from<R: std::io::Read>(mut input: R) -> Result<Self, CsvError> {
+        let invalid = |message: &str| CsvError::InvalidIndex(message.to_string());
+        let truncated = || invalid("truncated or malformed");
+        let mut bytes = Vec::new();
+        input.read_to_end(&mut bytes)?;
+        let mut rest = bytes.strip_prefix(INDEX_MAGIC).ok_or_else(|| invalid("not a CSV index"))?;
+        let (&version, tail) = rest.split_first().ok_or_else(truncated)?;
+        if version != INDEX_VERSION {
+            return Err(CsvError::InvalidIndex(format!(
+                "format {} is not supported (expected {})",
+                version, INDEX_VERSION
+            )));
+        }
+        rest = tail;
+        let mut next = || read_varint(&mut rest).ok_or_else(truncated);
+        let stride = next()?;
+        let records = next()?;
+        let expected_fields = next()?.checked_sub(1);
+        let headers = match next()?.checked_sub(1) {
+            None => None,
+            Some(count) => {
+                let mut names = Vec::new();
+                for _ in 0..count {
+                    let len = read_varint(&mut rest).ok_or_else(truncated)?;
+                    let name = rest.get(..len).ok_or_else(truncated)?;
+                    names.push(std::str::from_utf8(name).map_err(|_| invalid("a header name is not UTF-8"))?.to_string());
+                    rest = &rest[len..];
+                }
+                Some(names)
+            }
+        };
+        let mut next = || read_varint(&mut rest).ok_or_else(truncated);
+        let count = next()?;
+        if stride == 0 || count != records.div_ceil(stride) {
+            return Err(invalid("the entry count does not match the stride and record count"));
+        }
+        let mut entries = Vec::with_capacity(count);
+        let (mut byte_offset, mut line) = (0usize, 0usize);
+        for _ in 0..count {
+            byte_offset = byte_offset.checked_add(next()?).ok_or_else(truncated)?;
+            line = line.checked_add(next()?).ok_or_else(truncated)?;
+            entries.push(IndexEntry { byte_offset, line, column: next()? });
+        }
+        if !rest.is_empty() {
+            return Err(invalid("trailing bytes after the last entry"));
+        }
+        Ok(CsvIndex { stride, records, headers, expected_fields, entries })
+    }
+}
+
+/// LEB128: seven bits per byte, low bits first, high bit set on all but the last.
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(input: &mut &[u8]) -> Option<usize> {
+    let mut value = 0usize;
+    for shift in (0..usize::BITS).step_by(7) {
+        let (&byte, rest) = input.split_first()?;
+        *input = rest;
+        value |= usize::from(byte & 0x7f).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Builds a `CsvIndex` in one pass over a file.
+#[derive(Debug, Clone, Copy)]
+pub struct Indexer {
+    config: CsvConfig,
+    stride: usize,
+}
+
+impl Indexer {
+    pub fn new(config: CsvConfig) -> Self {
+        Indexer { config, stride: DEFAULT_INDEX_STRIDE }
+    }
+
+    /// Indexes every `stride`th record (default 1024): a smaller stride means
+    /// less parsing to reach a record, and a bigger index.
+    pub fn with_stride(mut self, stride: usize) -> Self {
+        self.stride = stride.max(1);
+        self
+    }
+
+    /// Parses `inner` to the end, noting where every `stride`th record starts.
+    /// Fails on the errors reading it with a `CsvReader` would.
+    pub fn index<R: std::io::Read>(&self, inner: R) -> Result<CsvIndex, CsvError> {
+        let mut reader = CsvReader::new(inner, self.config);
+        let mut record = StringRecord::default();
+        let mut entries = Vec::new();
+        let mut records = 0usize;
+        while reader.read_record_into(&mut record)? {
+            if records.is_multiple_of(self.stride) {
+                let Position { line, column, byte_offset, .. } = record.position();
+                entries.push(IndexEntry { byte_offset, line, column });
+            }
+            records += 1;
+        }
+        Ok(CsvIndex {
+            stride: self.stride,
+            records,
+            headers: reader.headers()?.map(<[String]>::to_vec),
+            expected_fields: reader.parser.expected_fields,
+            entries,
+        })
+    }
+}
+
+/// Reads records of a seekable file by number, using a `CsvIndex` to start
+/// parsing at the nearest indexed record instead of the top of the file.
+/// Records keep their positions in the whole file. Iterates from the
+/// position set by `seek_to_record` (the first record to begin with).
+pub struct IndexedReader<R: std::io::Read + std::io::Seek> {
+    reader: CsvReader<R>,
+    index: CsvIndex,
+    headers: Option<std::sync::Arc<HeaderIndex>>,
+    /// The record `next_record` returns, once the reader is positioned.
+    next: Option<usize>,
+}
+
+impl<R: std::io::Read + std::io::Seek> IndexedReader<R> {
+    /// `config` must be the one `index` was built with.
+    pub fn new(inner: R, config: CsvConfig, index: CsvIndex) -> Result<Self, CsvError> {
+        let headers = match &index.headers {
+            Some(names) => Some(std::sync::Arc::new(HeaderIndex::with_policy(names.clone(), config.duplicate_headers)?)),
+            None => None,
+        };
+        // Footer records are excluded by the index's record count instead.
+        let config = CsvConfig { skip_footer: 0, ..config };
+        Ok(IndexedReader { reader: CsvReader::new(inner, config), index, headers, next: None })
+    }
+
+    /// See `CsvReader::with_column`.
+    pub fn with_column(mut self, index: usize, column: ColumnConfig) -> Self {
+        self.reader = self.reader.with_column(index, column);
+        self
+    }
+
+    /// See `CsvReader::with_column_named`.
+    pub fn with_column_named(mut self, name: &str, column: ColumnConfig) -> Self {
+        self.reader = self.reader.with_column_named(name, column);
+        self
+    }
+
+    pub fn index(&self) -> &CsvIndex {
+        &self.index
+    }
+
+    /// See `CsvIndex::headers`.
+    pub fn headers(&self) -> Option<&[String]> {
+        self.index.headers()
+    }
+
+    /// Data records in the file.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Record `k`, or `None` past the end. Parses at most `stride` records
+    /// from the nearest indexed one.
+    pub fn record(&mut self, k: usize) -> Result<Option<StringRecord>, CsvError> {
+        self.seek_to_record(k)?;
+        self.next_record()
+    }
+
+    /// Positions the reader so that `next_record` returns record `n` (or
+    /// `None`, past the end). Seeks to the nearest indexed record and parses
+    /// up to `n` from there, unless `n` is just ahead of the current position.
+    pub fn seek_to_record(&mut self, n: usize) -> Result<(), CsvError> {
+        let n = n.min(self.index.len());
+        let next = self.next.take();
+        if n == self.index.len() {
+            self.next = Some(n);
+            return Ok(());
+        }
+        // Parsing on from where the reader is beats seeking back to an
+        // indexed record it has already passed.
+        let indexed = n / self.index.stride * self.index.stride;
+        if !next.is_some_and(|next| (indexed..=n).contains(&next)) {
+            let start = self
+                .index
+                .entry_before(n)
+                .ok_or_else(|| CsvError::InvalidIndex(format!("no indexed record at or before record {}", n)))?;
+            self.reader.restart_at(start, self.headers.clone(), self.index.expected_fields)?;
+        }
+        self.reader.skip_to_record(n)?;
+        self.next = Some(n);
+        Ok(())
+    }
+
+    /// Records `range.start` up to (not including) `range.end`, cut off at
+    /// the end of the file. Leaves the reader positioned after them.
+    pub fn records_range(&mut self, range: std::ops::Range<usize>) -> Result<Vec<StringRecord>, CsvError> {
+        let end = range.end.min(self.index.len());
+        self.seek_to_record(range.start)?;
+        let mut records = Vec::with_capacity(end.saturating_sub(range.start));
+        while self.next.is_some_and(|next| next < end)
+            && let Some(record) = self.next_record()?
+        {
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    /// The record at the current position, moving past it.
+    pub fn next_record(&mut self) -> Result<Option<StringRecord>, CsvError> {
+        let next = match self.next {
+            Some(next) => next,
+            None => {
+                self.seek_to_record(0)?;
+                0
+            }
+        };
+        if next >= self.index.len() {
+            return Ok(None);
+        }
+        self.next = None;
+        let record = self.reader.next_record()?;
+        if record.is_some() {
+            self.next = Some(next + 1);
+        }
+        Ok(record)
+    }
+}
+
+impl<R: std::io::Read + std::io::Seek> Iterator for IndexedReader<R> {
+    type Item = Result<StringRecord, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
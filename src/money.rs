@@ -0,0 +1,50 @@
+//! Exact `rust_decimal::Decimal` parsing for monetary columns, tolerant of
+//! surrounding whitespace and a currency symbol, e.g. `" £25.99 "` or `"-$3.50"`.
+//! Use with serde as `#[serde(with = "rust_csv_parser::money")]`, or
+//! `rust_csv_parser::money::option` for nullable columns.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer};
+
+const CURRENCY_SYMBOLS: &[char] = &['$', '£', '€', '¥', '₹', '₩', '₽', '¢'];
+
+/// Parses an amount such as `"25.99"`, `"£25.99"`, `"-€ 3.50"` or `"12.00$"`.
+pub fn parse_money(value: &str) -> Option<Decimal> {
+    let value = value.trim();
+    let (negative, unsigned) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, value.strip_prefix('+').unwrap_or(value).trim_start()),
+    };
+    let amount = unsigned
+        .strip_prefix(CURRENCY_SYMBOLS)
+        .or_else(|| unsigned.strip_suffix(CURRENCY_SYMBOLS))
+        .unwrap_or(unsigned)
+        .trim();
+    if amount.starts_with(['-', '+']) {
+        return None;
+    }
+    let amount: Decimal = amount.parse().ok()?;
+    Some(if negative { -amount } else { amount })
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+    let text = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+    parse_money(&text).ok_or_else(|| serde::de::Error::custom(format!("cannot parse {:?} as an amount", text)))
+}
+
+/// Like `money::deserialize`, with empty fields and null tokens as `None`.
+pub mod option {
+    use super::*;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Decimal>, D::Error> {
+        let Some(text) = <Option<std::borrow::Cow<'de, str>>>::deserialize(deserializer)? else {
+            return Ok(None);
+        };
+        if text.trim().is_empty() {
+            return Ok(None);
+        }
+        parse_money(&text)
+            .map(Some)
+            .ok_or_else(|| serde::de::Error::custom(format!("cannot parse {:?} as an amount", text)))
+    }
+}
@@ -0,0 +1,139 @@
+//! `parse_parallel`, which parses an in-memory input across the rayon thread pool.
+
+use super::*;
+
+/// Inputs are only split into segments of at least this many bytes.
+const MIN_PARALLEL_SEGMENT: usize = 1 << 20;
+
+/// Parses an in-memory input on the rayon thread pool, with the same records
+/// (and record positions) as reading it with `CsvReader`.
+///
+/// The input is cut after line breaks that an even number of quote
+/// characters precede, segments are parsed concurrently, and the records are
+/// stitched back in order. A cut is only kept if the parser of the segment
+/// before it ends there between records, so quoting the prescan misjudges
+/// (escaped or bare quotes) cannot split a field. Anything the segments
+/// cannot reproduce exactly is parsed on one thread instead: `skip_footer`,
+/// `max_records`, parse errors, skipped records and invalid UTF-8.
+/// Warnings are not reported.
+pub fn parse_parallel(input: &[u8], config: CsvConfig) -> Result<Vec<StringRecord>, CsvError> {
+    let segments = rayon::current_num_threads().min(input.len() / MIN_PARALLEL_SEGMENT);
+    if segments < 2 || config.skip_footer > 0 || config.max_records.is_some() {
+        return CsvReader::new(input, config).collect();
+    }
+    let quote = config.quote.filter(char::is_ascii).map(|quote| quote as u8);
+    match parse_segments(input, config, &segment_bounds(input, quote, segments)) {
+        Some(records) => Ok(records),
+        None => CsvReader::new(input, config).collect(),
+    }
+}
+
+/// Start offsets of the segments: 0, then the first line break after each
+/// `len / segments` step that is outside quotes by quote parity.
+pub(crate) fn segment_bounds(input: &[u8], quote: Option<u8>, segments: usize) -> Vec<usize> {
+    use rayon::prelude::*;
+
+    let step = input.len() / segments;
+    let blocks: Vec<&[u8]> = input.chunks(step).collect();
+    let quotes: Vec<usize> = match quote {
+        Some(quote) => blocks.par_iter().map(|block| memchr::memchr_iter(quote, block).count()).collect(),
+        None => vec![0; blocks.len()],
+    };
+    let mut bounds = vec![0];
+    let mut odd = false;
+    for (i, count) in quotes.iter().enumerate().take(blocks.len() - 1) {
+        odd ^= count % 2 == 1;
+        let mut at = (i + 1) * step;
+        let mut in_quotes = odd;
+        let cut = loop {
+            let rest = &input[at..];
+            let found = match quote {
+                Some(quote) => memchr::memchr2(quote, b'\n', rest),
+                None => memchr::memchr(b'\n', rest),
+            };
+            let Some(found) = found else { break None };
+            at += found + 1;
+            if input[at - 1] != b'\n' {
+                in_quotes = !in_quotes;
+            } else if !in_quotes {
+                break Some(at);
+            }
+        };
+        match cut {
+            Some(cut) if cut < input.len() && cut > *bounds.last().unwrap() => bounds.push(cut),
+            _ => {}
+        }
+    }
+    bounds
+}
+
+/// Parses each segment with its own parser and stitches the records, or
+/// `None` if a segment boundary turns out to be inside a record or the
+/// segments would not match a sequential parse.
+pub(crate) fn parse_segments(input: &[u8], config: CsvConfig, bounds: &[usize]) -> Option<Vec<StringRecord>> {
+    use rayon::prelude::*;
+
+    struct Segment {
+        records: Vec<StringRecord>,
+        lines: usize,
+        headers: Option<std::sync::Arc<HeaderIndex>>,
+    }
+
+    let ends = bounds.iter().skip(1).copied().chain(std::iter::once(input.len()));
+    let ranges: Vec<_> = bounds.iter().copied().zip(ends).collect();
+    let segments = ranges
+        .par_iter()
+        .enumerate()
+        .map(|(i, &(start, end))| {
+            let text = std::str::from_utf8(&input[start..end]).ok()?;
+            let first = i == 0;
+            let last = end == input.len();
+            let config = if first { config } else { CsvConfig { skip_rows: 0, has_headers: false, ..config } };
+            let mut parser = CsvChunkParser::new(config);
+            let mut result = parser.process_chunk(text).ok()?;
+            if last {
+                let tail = parser.process_chunk("").ok()?;
+                result.complete_rows.extend(tail.complete_rows);
+                result.errors.extend(tail.errors);
+            } else if parser.bytes_consumed() != text.len() || parser.lines_to_skip > 0 {
+                return None;
+            }
+            if !result.errors.is_empty() || parser.records_skipped() > 0 {
+                return None;
+            }
+            if first && config.has_headers && parser.headers.is_none() {
+                return None;
+            }
+            let lines = memchr::memchr_iter(b'\n', text.as_bytes()).count();
+            Some(Segment { records: result.complete_rows, lines, headers: parser.headers })
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let headers = segments[0].headers.clone();
+    let expected_fields = match &headers {
+        Some(headers) => Some(headers.names().len()),
+        None => segments.iter().flat_map(|segment| segment.records.first()).next().map(StringRecord::len),
+    };
+    let mut records = Vec::with_capacity(segments.iter().map(|segment| segment.records.len()).sum());
+    let mut lines_before = 0;
+    for (segment, &(start, _)) in segments.into_iter().zip(&ranges) {
+        let shift = records.len();
+        for mut record in segment.records {
+            if config.strict_field_count && Some(record.len()) != expected_fields {
+                return None;
+            }
+            if start > 0 {
+                record.record_index += shift;
+                record.byte_offset += start;
+                record.line += lines_before;
+                record.headers = headers.clone();
+                for span in &mut record.spans {
+                    *span = span.start + start..span.end + start;
+                }
+            }
+            records.push(record);
+        }
+        lines_before += segment.lines;
+    }
+    Some(records)
+}
@@ -0,0 +1,233 @@
+//! `PipelinedReader`, which reads and scans the input on a background thread.
+
+use super::*;
+use crate::reader::DEFAULT_READ_CHUNK_SIZE;
+
+/// Batches in flight between the stages of a `PipelinedReader`.
+const DEFAULT_PIPELINE_DEPTH: usize = 4;
+
+/// Records the materializer hands over at a time.
+const PIPELINE_RECORD_BATCH: usize = 1024;
+
+/// The scanner passes on text with no record boundary once it grows past
+/// this, so a runaway quote cannot buffer the whole stream.
+const PIPELINE_MAX_PENDING: usize = 16 * DEFAULT_READ_CHUNK_SIZE;
+
+/// A record reader split into two threads joined by bounded queues: one
+/// reads the input and cuts it into runs of whole records, the other parses
+/// those into `StringRecord`s while the next run is read. Iterates like
+/// `CsvReader`; the threads start on the first read.
+pub struct PipelinedReader<R: std::io::Read + Send + 'static> {
+    /// The input and config, until the threads start.
+    source: Option<(R, CsvConfig)>,
+    chunk_size: usize,
+    depth: usize,
+    records: Option<std::sync::mpsc::Receiver<Result<Vec<StringRecord>, CsvError>>>,
+    scanner: Option<std::thread::JoinHandle<()>>,
+    materializer: Option<std::thread::JoinHandle<()>>,
+    batch: std::vec::IntoIter<StringRecord>,
+}
+
+impl<R: std::io::Read + Send + 'static> PipelinedReader<R> {
+    pub fn new(inner: R, config: CsvConfig) -> Self {
+        PipelinedReader {
+            source: Some((inner, config)),
+            chunk_size: DEFAULT_READ_CHUNK_SIZE,
+            depth: DEFAULT_PIPELINE_DEPTH,
+            records: None,
+            scanner: None,
+            materializer: None,
+            batch: Vec::new().into_iter(),
+        }
+    }
+
+    /// Bytes the scanner reads at a time.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// How many batches each queue holds before its producer waits.
+    pub fn with_queue_depth(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    fn start(&mut self) {
+        let Some((inner, config)) = self.source.take() else { return };
+        let (text_tx, text_rx) = std::sync::mpsc::sync_channel(self.depth);
+        let (record_tx, record_rx) = std::sync::mpsc::sync_channel(self.depth);
+        let quote = config.quote.filter(char::is_ascii).map(|quote| quote as u8);
+        let chunk_size = self.chunk_size;
+        self.scanner = Some(std::thread::spawn(move || scan_record_runs(inner, quote, chunk_size, text_tx)));
+        self.materializer = Some(std::thread::spawn(move || {
+            materialize_records(CsvReader::new(QueuedInput::new(text_rx), config).with_chunk_size(chunk_size), record_tx)
+        }));
+        self.records = Some(record_rx);
+    }
+
+    pub fn next_record(&mut self) -> Result<Option<StringRecord>, CsvError> {
+        loop {
+            if let Some(record) = self.batch.next() {
+                return Ok(Some(record));
+            }
+            self.start();
+            let Some(records) = &self.records else { return Ok(None) };
+            match records.recv() {
+                Ok(Ok(batch)) => self.batch = batch.into_iter(),
+                Ok(Err(e)) => {
+                    self.records = None;
+                    // I/O errors come from the scanner, which has stopped by
+                    // then: after an input error, or by panicking.
+                    if matches!(e, CsvError::Io(_))
+                        && let Some(Err(panic)) = self.scanner.take().map(std::thread::JoinHandle::join)
+                    {
+                        std::panic::resume_unwind(panic);
+                    }
+                    return Err(e);
+                }
+                Err(_) => {
+                    self.records = None;
+                    // The queue closes early only if the materializer panicked.
+                    if let Some(Err(panic)) = self.materializer.take().map(std::thread::JoinHandle::join) {
+                        std::panic::resume_unwind(panic);
+                    }
+                    return Ok(None);
+                }
+            }
+        }
+    }
+}
+
+impl<R: std::io::Read + Send + 'static> Iterator for PipelinedReader<R> {
+    type Item = Result<StringRecord, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+/// Scanner stage: reads `inner` and sends the text up to the last line break
+/// outside quotes (by quote parity) of what has been read so far. The cuts
+/// only decide batching; the parser sees one continuous stream either way.
+/// An empty run marks the end of the input.
+fn scan_record_runs<R: std::io::Read>(
+    mut inner: R,
+    quote: Option<u8>,
+    chunk_size: usize,
+    text: std::sync::mpsc::SyncSender<std::io::Result<Vec<u8>>>,
+) {
+    let mut pending = Vec::new();
+    let mut in_quotes = false;
+    let mut buf = vec![0u8; chunk_size];
+    loop {
+        let read = match inner.read(&mut buf) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                let _ = text.send(Err(e));
+                return;
+            }
+        };
+        let scanned = pending.len();
+        pending.extend_from_slice(&buf[..read]);
+        let mut cut = None;
+        let new = &pending[scanned..];
+        // Without quoting, every line break is a boundary.
+        for at in memchr::memchr2_iter(quote.unwrap_or(b'\n'), b'\n', new) {
+            if new[at] != b'\n' {
+                in_quotes = !in_quotes;
+            } else if !in_quotes {
+                cut = Some(scanned + at + 1);
+            }
+        }
+        let cut = match cut {
+            Some(cut) => cut,
+            None if pending.len() > PIPELINE_MAX_PENDING => pending.len(),
+            None => continue,
+        };
+        let rest = pending.split_off(cut);
+        if text.send(Ok(std::mem::replace(&mut pending, rest))).is_err() {
+            return;
+        }
+    }
+    if !pending.is_empty() && text.send(Ok(pending)).is_err() {
+        return;
+    }
+    let _ = text.send(Ok(Vec::new()));
+}
+
+/// Materializer stage: parses the scanner's text and sends the records in
+/// batches, ending with the first error.
+fn materialize_records<R: std::io::Read>(
+    mut reader: CsvReader<R>,
+    records: std::sync::mpsc::SyncSender<Result<Vec<StringRecord>, CsvError>>,
+) {
+    let mut batch = Vec::with_capacity(PIPELINE_RECORD_BATCH);
+    loop {
+        match reader.next_record() {
+            Ok(Some(record)) => {
+                batch.push(record);
+                if batch.len() < PIPELINE_RECORD_BATCH {
+                    continue;
+                }
+            }
+            Ok(None) => {
+                if !batch.is_empty() {
+                    let _ = records.send(Ok(batch));
+                }
+                return;
+            }
+            Err(e) => {
+                if !batch.is_empty() && records.send(Ok(std::mem::take(&mut batch))).is_err() {
+                    return;
+                }
+                let _ = records.send(Err(e));
+                return;
+            }
+        }
+        let full = std::mem::replace(&mut batch, Vec::with_capacity(PIPELINE_RECORD_BATCH));
+        if records.send(Ok(full)).is_err() {
+            return;
+        }
+    }
+}
+
+/// `std::io::Read` over the text the scanner sends; EOF once it is done. A
+/// scanner gone without marking the end (it panicked) is an error, so a
+/// truncated stream never reads as a complete one.
+struct QueuedInput {
+    text: std::sync::mpsc::Receiver<std::io::Result<Vec<u8>>>,
+    current: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl QueuedInput {
+    fn new(text: std::sync::mpsc::Receiver<std::io::Result<Vec<u8>>>) -> Self {
+        QueuedInput { text, current: Vec::new(), pos: 0, done: false }
+    }
+}
+
+impl std::io::Read for QueuedInput {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.current.len() {
+            if self.done {
+                return Ok(0);
+            }
+            match self.text.recv() {
+                Ok(text) => {
+                    self.current = text?;
+                    self.pos = 0;
+                    self.done = self.current.is_empty();
+                }
+                Err(_) => return Err(std::io::Error::other("the input scanner stopped before the end of the input")),
+            }
+        }
+        let len = buf.len().min(self.current.len() - self.pos);
+        buf[..len].copy_from_slice(&self.current[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
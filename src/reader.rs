@@ -0,0 +1,314 @@
+//! `CsvReader`, which streams records from any `std::io::Read`.
+
+use super::*;
+
+pub(crate) const DEFAULT_READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Character encoding of the bytes fed into `CsvReader` and `CsvRewriter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputEncoding {
+    #[default]
+    Utf8,
+    /// ISO-8859-1: every byte maps directly to the code point of the same value.
+    Latin1,
+}
+
+/// `String::from_utf8`, validating with SIMD instructions when the
+/// `simdutf8` feature is enabled. Invalid input is checked again by
+/// `String::from_utf8`, for its detailed error.
+#[inline]
+pub(crate) fn string_from_utf8(bytes: Vec<u8>) -> Result<String, std::string::FromUtf8Error> {
+    #[cfg(feature = "simdutf8")]
+    if simdutf8::basic::from_utf8(&bytes).is_ok() {
+        // SAFETY: `bytes` was just validated as UTF-8.
+        return Ok(unsafe { String::from_utf8_unchecked(bytes) });
+    }
+    String::from_utf8(bytes)
+}
+
+/// `std::str::from_utf8`, validating like `string_from_utf8`.
+#[inline]
+pub(crate) fn str_from_utf8(bytes: &[u8]) -> Result<&str, std::str::Utf8Error> {
+    #[cfg(feature = "simdutf8")]
+    if let Ok(text) = simdutf8::basic::from_utf8(bytes) {
+        return Ok(text);
+    }
+    std::str::from_utf8(bytes)
+}
+
+/// Splits off the longest valid UTF-8 prefix of `bytes`, leaving an incomplete
+/// trailing sequence behind to be completed by the next read.
+pub(crate) fn decode_utf8_prefix(bytes: &mut Vec<u8>) -> Result<String, CsvError> {
+    match string_from_utf8(std::mem::take(bytes)) {
+        Ok(text) => Ok(text),
+        Err(e) if e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut text = e.into_bytes();
+            *bytes = text.split_off(valid);
+            Ok(string_from_utf8(text)?)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Pull-based record reader over any `std::io::Read`, driving a
+/// `CsvChunkParser` one chunk at a time.
+pub struct CsvReader<R: std::io::Read> {
+    pub(crate) inner: R,
+    pub(crate) parser: CsvChunkParser,
+    encoding: InputEncoding,
+    read_buf: Vec<u8>,
+    /// Undecoded bytes of a UTF-8 sequence split across reads.
+    pending: Vec<u8>,
+    queue: std::collections::VecDeque<StringRecord>,
+    /// Skipped records reported so far (`ErrorPolicy::Skip`).
+    errors: Vec<RecordError>,
+    /// Warnings not yet taken, up to `CsvConfig::max_reported_errors`.
+    warnings: Vec<ParseWarning>,
+    eof: bool,
+    /// When set, records are counted here instead of queued (`count_records`).
+    pub(crate) count_only: Option<RecordCount>,
+}
+
+/// Counts records handed to it, dropping their fields.
+#[derive(Debug, Default)]
+pub(crate) struct RecordCount(pub(crate) usize);
+
+impl RecordSink for RecordCount {
+    fn field(&mut self, _: &str) {}
+
+    fn end_record(&mut self) {
+        self.0 += 1;
+    }
+}
+
+impl<R: std::io::Read> CsvReader<R> {
+    pub fn new(inner: R, config: CsvConfig) -> Self {
+        CsvReader {
+            inner,
+            parser: CsvChunkParser::new(config),
+            encoding: InputEncoding::default(),
+            read_buf: vec![0u8; DEFAULT_READ_CHUNK_SIZE],
+            pending: Vec::new(),
+            queue: std::collections::VecDeque::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            eof: false,
+            count_only: None,
+        }
+    }
+
+    /// Like `new`, but fails if `config` does not pass `CsvConfig::validate`.
+    pub fn try_new(inner: R, config: CsvConfig) -> Result<Self, CsvError> {
+        config.validate()?;
+        Ok(CsvReader::new(inner, config))
+    }
+
+    pub fn with_input_encoding(mut self, encoding: InputEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.read_buf = vec![0u8; chunk_size.max(1)];
+        self
+    }
+
+    /// See `CsvChunkParser::with_column`.
+    pub fn with_column(mut self, index: usize, column: ColumnConfig) -> Self {
+        self.parser = self.parser.with_column(index, column);
+        self
+    }
+
+    /// See `CsvChunkParser::with_column_named`.
+    pub fn with_column_named(mut self, name: &str, column: ColumnConfig) -> Self {
+        self.parser = self.parser.with_column_named(name, column);
+        self
+    }
+
+    /// Reads until the header row is known. `None` without `has_headers`, or for empty input.
+    pub fn headers(&mut self) -> Result<Option<&[String]>, CsvError> {
+        if self.parser.config.has_headers {
+            while self.parser.headers().is_none() && !self.eof {
+                self.fill()?;
+            }
+        }
+        Ok(self.parser.headers())
+    }
+
+    /// Malformed records dropped so far under `ErrorPolicy::Skip`.
+    pub fn records_skipped(&self) -> usize {
+        self.parser.records_skipped()
+    }
+
+    /// The malformed records skipped so far; reported errors are handed over
+    /// once, so a later call only returns newer ones.
+    pub fn take_error_report(&mut self) -> ErrorReport {
+        ErrorReport { records_skipped: self.records_skipped(), errors: std::mem::take(&mut self.errors) }
+    }
+
+    /// Warnings found since the last call. Only the first
+    /// `CsvConfig::max_reported_errors` are kept between calls.
+    pub fn take_warnings(&mut self) -> Vec<ParseWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    fn enqueue(&mut self, result: ChunkResult) {
+        self.queue.extend(result.complete_rows);
+        self.errors.extend(result.errors);
+        let room = self.parser.config.max_reported_errors.saturating_sub(self.warnings.len());
+        self.warnings.extend(result.warnings.into_iter().take(room));
+    }
+
+    pub fn next_record(&mut self) -> Result<Option<StringRecord>, CsvError> {
+        loop {
+            if let Some(record) = self.queue.pop_front() {
+                return Ok(Some(record));
+            }
+            if self.eof {
+                return Ok(None);
+            }
+            self.fill()?;
+        }
+    }
+
+    /// Reads the next record into `record`, returning `false` at the end of
+    /// the input. The previous contents of `record` are recycled, so the
+    /// parser refills their buffers rather than allocating: reusing one record
+    /// for a whole stream reads it with no allocations per record once warmed up.
+    pub fn read_record_into(&mut self, record: &mut StringRecord) -> Result<bool, CsvError> {
+        match self.next_record()? {
+            Some(next) => {
+                self.parser.recycle(std::mem::replace(record, next));
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Like `read_record_into`, for a `PackedRecord`: its storage is reused,
+    /// so once it has grown to fit the widest record no reads allocate.
+    pub fn read_packed_into(&mut self, record: &mut PackedRecord) -> Result<bool, CsvError> {
+        match self.next_record()? {
+            Some(next) => {
+                record.clear();
+                for field in &next {
+                    record.push_field(field);
+                }
+                self.parser.recycle(next);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Reads the next record as exactly `N` fields, failing with
+    /// `CsvError::UnequalLengths` on any other field count.
+    pub fn next_record_fixed<const N: usize>(&mut self) -> Result<Option<[String; N]>, CsvError> {
+        match self.next_record()? {
+            Some(record) => record.into_array().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses the pending bytes up to each invalid UTF-8 sequence, so the error
+    /// for it has an accurate position, then reports it or drops the record it
+    /// is in (`ErrorPolicy::Skip`). An incomplete sequence at the end is left
+    /// for the next read.
+    fn parse_to_invalid_utf8(&mut self) -> Result<(), CsvError> {
+        while let Err(e) = str_from_utf8(&self.pending) {
+            let Some(len) = e.error_len() else { break };
+            let valid = e.valid_up_to();
+            let excerpt = utf8_excerpt(&self.pending, valid, len);
+            let text = std::str::from_utf8(&self.pending[..valid]).expect("validated prefix");
+            if !text.is_empty() {
+                let result = Self::parse(&mut self.parser, &mut self.count_only, text)?;
+                self.enqueue(result);
+            }
+            self.pending.drain(..valid);
+            let error = std::str::from_utf8(&self.pending[..len]).unwrap_err();
+            self.parser.invalid_utf8(error, excerpt, len)?;
+            self.pending.drain(..len);
+        }
+        Ok(())
+    }
+
+    fn parse(parser: &mut CsvChunkParser, count_only: &mut Option<RecordCount>, text: &str) -> Result<ChunkResult, CsvError> {
+        match count_only {
+            Some(count) => parser.process_chunk_into(text, count),
+            None => parser.process_chunk(text),
+        }
+    }
+
+    /// Reads and parses one chunk, queueing any records it completes.
+    fn fill(&mut self) -> Result<(), CsvError> {
+        let bytes_read = self.inner.read(&mut self.read_buf)?;
+        if bytes_read == 0 {
+            self.eof = true;
+            // A multi-byte sequence cut off by EOF is invalid UTF-8.
+            if !self.pending.is_empty() {
+                let len = self.pending.len();
+                let error = std::str::from_utf8(&self.pending).unwrap_err();
+                let excerpt = utf8_excerpt(&self.pending, 0, len);
+                self.parser.invalid_utf8(error, excerpt, len)?;
+                self.pending.clear();
+            }
+            let result = Self::parse(&mut self.parser, &mut self.count_only, "")?;
+            self.enqueue(result);
+            return Ok(());
+        }
+
+        let text = match self.encoding {
+            InputEncoding::Utf8 => {
+                self.pending.extend_from_slice(&self.read_buf[..bytes_read]);
+                self.parse_to_invalid_utf8()?;
+                decode_utf8_prefix(&mut self.pending)?
+            }
+            InputEncoding::Latin1 => self.read_buf[..bytes_read].iter().map(|&b| b as char).collect(),
+        };
+        if !text.is_empty() {
+            let result = Self::parse(&mut self.parser, &mut self.count_only, &text)?;
+            self.enqueue(result);
+        }
+        // With `max_records`, there is no need to read the rest of the input.
+        self.eof |= self.parser.limit_reached();
+        Ok(())
+    }
+}
+
+impl<R: std::io::Read> Iterator for CsvReader<R> {
+    type Item = Result<StringRecord, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+impl<R: std::io::Read + std::io::Seek> CsvReader<R> {
+    /// Seeks to `position`, a record boundary, and parses on from there.
+    pub(crate) fn restart_at(&mut self, position: Position, headers: Option<std::sync::Arc<HeaderIndex>>, expected_fields: Option<usize>) -> Result<(), CsvError> {
+        self.inner.seek(std::io::SeekFrom::Start(position.byte_offset as u64))?;
+        self.parser.start_at(position, headers, expected_fields);
+        self.pending.clear();
+        self.queue.clear();
+        self.eof = false;
+        Ok(())
+    }
+
+    /// Reads past the records before record `n`, recycling them.
+    pub(crate) fn skip_to_record(&mut self, n: usize) -> Result<(), CsvError> {
+        loop {
+            while let Some(record) = self.queue.front() {
+                if record.record_index() >= n {
+                    return Ok(());
+                }
+                let record = self.queue.pop_front().expect("front checked");
+                self.parser.recycle(record);
+            }
+            if self.eof {
+                return Ok(());
+            }
+            self.fill()?;
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Best-effort rescue of malformed input: `repair::repair` rewrites it as valid
+//! CSV and logs each change it had to make.
+
+use super::{CsvConfig, CsvError, CsvWriter};
+use std::io::BufRead;
+
+/// Which fixes `repair` may apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepairOptions {
+    /// Close a quote still open at the end of its line, so every record is
+    /// one line. Without it, quoted fields may span lines and only a quote
+    /// open at EOF is closed.
+    pub close_quotes_at_line_end: bool,
+    /// Keep stray quotes (`a"b`, `"a"b"`) as data. Without it they are removed.
+    pub escape_stray_quotes: bool,
+    /// Fill records shorter than the first one with empty fields.
+    pub pad_ragged_rows: bool,
+}
+
+impl Default for RepairOptions {
+    fn default() -> Self {
+        RepairOptions { close_quotes_at_line_end: true, escape_stray_quotes: true, pad_ragged_rows: true }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RepairKind {
+    ClosedQuote,
+    EscapedQuote,
+    RemovedQuote,
+    PaddedRow { added: usize },
+    /// Invalid UTF-8 replaced with U+FFFD.
+    ReplacedInvalidUtf8,
+}
+
+/// One change made by `repair`, on a 1-based input line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repair {
+    pub line: usize,
+    pub kind: RepairKind,
+}
+
+impl std::fmt::Display for Repair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match self.kind {
+            RepairKind::ClosedQuote => f.write_str("closed a quote left open"),
+            RepairKind::EscapedQuote => f.write_str("kept a stray quote as data"),
+            RepairKind::RemovedQuote => f.write_str("removed a stray quote"),
+            RepairKind::PaddedRow { added } => write!(f, "padded the record with {} empty fields", added),
+            RepairKind::ReplacedInvalidUtf8 => f.write_str("replaced invalid UTF-8"),
+        }
+    }
+}
+
+/// Splits lines into fields leniently, noting what it had to fix.
+struct Tokenizer {
+    delimiter: char,
+    quote: Option<char>,
+    options: RepairOptions,
+    fields: Vec<String>,
+    field: String,
+    at_field_start: bool,
+    in_quotes: bool,
+}
+
+impl Tokenizer {
+    fn stray_quote(&mut self, quote: char, line: usize, log: &mut Vec<Repair>) {
+        let kind = if self.options.escape_stray_quotes {
+            self.field.push(quote);
+            RepairKind::EscapedQuote
+        } else {
+            RepairKind::RemovedQuote
+        };
+        log.push(Repair { line, kind });
+    }
+
+    fn feed_line(&mut self, text: &str, line: usize, log: &mut Vec<Repair>) {
+        let mut chars = text.chars().peekable();
+        while let Some(ch) = chars.next() {
+            let is_quote = Some(ch) == self.quote;
+            if self.in_quotes {
+                if !is_quote {
+                    self.field.push(ch);
+                } else if chars.peek() == Some(&ch) {
+                    self.field.push(ch);
+                    chars.next();
+                } else if chars.peek().is_none_or(|&next| next == self.delimiter) {
+                    self.in_quotes = false;
+                } else {
+                    self.stray_quote(ch, line, log);
+                }
+            } else if ch == self.delimiter {
+                self.fields.push(std::mem::take(&mut self.field));
+                self.at_field_start = true;
+            } else if is_quote && self.at_field_start {
+                self.in_quotes = true;
+                self.at_field_start = false;
+            } else if is_quote {
+                self.stray_quote(ch, line, log);
+            } else {
+                self.field.push(ch);
+                self.at_field_start = false;
+            }
+        }
+    }
+
+    fn finish_record(&mut self) -> Vec<String> {
+        self.fields.push(std::mem::take(&mut self.field));
+        self.at_field_start = true;
+        std::mem::take(&mut self.fields)
+    }
+}
+
+/// Reads `reader` line by line in `config`'s dialect and writes it to
+/// `writer` as valid CSV, returning the changes made. Quotes are escaped by
+/// doubling; blank lines are dropped. Fails only on I/O errors.
+pub fn repair<R: std::io::Read, W: std::io::Write>(
+    reader: R,
+    writer: W,
+    config: CsvConfig,
+    options: RepairOptions,
+) -> Result<Vec<Repair>, CsvError> {
+    let mut reader = std::io::BufReader::new(reader);
+    let mut writer = CsvWriter::new(writer, config);
+    let mut tokenizer = Tokenizer {
+        delimiter: config.delimiter,
+        quote: config.quote,
+        options,
+        fields: Vec::new(),
+        field: String::new(),
+        at_field_start: true,
+        in_quotes: false,
+    };
+    let mut log = Vec::new();
+    let mut width = None;
+    let mut write = |mut fields: Vec<String>, line: usize, log: &mut Vec<Repair>| -> Result<(), CsvError> {
+        if fields.len() == 1 && fields[0].is_empty() {
+            return Ok(());
+        }
+        let width = *width.get_or_insert(fields.len());
+        if options.pad_ragged_rows && fields.len() < width {
+            log.push(Repair { line, kind: RepairKind::PaddedRow { added: width - fields.len() } });
+            fields.resize(width, String::new());
+        }
+        writer.write_record(&fields)
+    };
+    let mut buf = Vec::new();
+    let mut line = 0;
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        line += 1;
+        let text = String::from_utf8_lossy(&buf);
+        if let std::borrow::Cow::Owned(_) = text {
+            log.push(Repair { line, kind: RepairKind::ReplacedInvalidUtf8 });
+        }
+        let text = text.strip_suffix('\n').unwrap_or(&text);
+        let text = text.strip_suffix('\r').unwrap_or(text);
+        tokenizer.feed_line(text, line, &mut log);
+        if tokenizer.in_quotes {
+            if !options.close_quotes_at_line_end {
+                tokenizer.field.push('\n');
+                continue;
+            }
+            log.push(Repair { line, kind: RepairKind::ClosedQuote });
+            tokenizer.in_quotes = false;
+        }
+        write(tokenizer.finish_record(), line, &mut log)?;
+    }
+    if tokenizer.in_quotes {
+        log.push(Repair { line, kind: RepairKind::ClosedQuote });
+        tokenizer.field.pop();
+        write(tokenizer.finish_record(), line, &mut log)?;
+    }
+    writer.flush()?;
+    Ok(log)
+}
@@ -0,0 +1,339 @@
+//! Serde serialization of values into records, for `CsvWriter::serialize`.
+
+use serde::ser::{self, Impossible, Serialize, SerializeSeq, SerializeStruct, SerializeTuple, SerializeTupleStruct};
+
+/// Why a value could not be serialized as a record, and which field (if
+/// known) was at fault.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerializeError {
+    pub field: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.field {
+            Some(field) => write!(f, "field {}: {}", field, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for SerializeError {}
+
+impl ser::Error for SerializeError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        SerializeError { field: None, message: msg.to_string() }
+    }
+}
+
+/// The fields of one record and, when it is a struct, their column names.
+#[derive(Debug, Default)]
+pub(crate) struct SerializedRecord {
+    pub(crate) fields: Vec<String>,
+    pub(crate) names: Vec<String>,
+}
+
+impl SerializedRecord {
+    fn unsupported(&self, what: &str) -> SerializeError {
+        SerializeError { field: Some(self.fields.len()), message: format!("{} cannot be written as a field", what) }
+    }
+}
+
+/// Turns a whole value into a record: structs, tuples and sequences give
+/// a field per element, anything else a record of one field.
+pub(crate) struct RecordSerializer<'a> {
+    pub(crate) record: &'a mut SerializedRecord,
+}
+
+/// Turns one value into a field, named when it belongs to a struct. A
+/// named struct is flattened into its fields, with the name as a prefix.
+struct FieldSerializer<'a> {
+    record: &'a mut SerializedRecord,
+    name: Option<String>,
+}
+
+impl FieldSerializer<'_> {
+    fn push(self, text: String) -> Result<(), SerializeError> {
+        self.record.fields.push(text);
+        if let Some(name) = self.name {
+            self.record.names.push(name);
+        }
+        Ok(())
+    }
+}
+
+/// The fields of a struct, each named `prefix` followed by its key.
+pub(crate) struct StructFields<'a> {
+    record: &'a mut SerializedRecord,
+    prefix: String,
+}
+
+/// The elements of a tuple or sequence, unnamed.
+pub(crate) struct SeqFields<'a> {
+    record: &'a mut SerializedRecord,
+}
+
+macro_rules! to_field {
+    ($($method:ident: $ty:ty),*) => {
+        $(fn $method(self, value: $ty) -> Result<(), SerializeError> {
+            self.push(value.to_string())
+        })*
+    };
+}
+
+impl<'a> ser::Serializer for FieldSerializer<'a> {
+    type Ok = ();
+    type Error = SerializeError;
+    type SerializeSeq = Impossible<(), SerializeError>;
+    type SerializeTuple = Impossible<(), SerializeError>;
+    type SerializeTupleStruct = Impossible<(), SerializeError>;
+    type SerializeTupleVariant = Impossible<(), SerializeError>;
+    type SerializeMap = Impossible<(), SerializeError>;
+    type SerializeStruct = StructFields<'a>;
+    type SerializeStructVariant = Impossible<(), SerializeError>;
+
+    to_field! {
+        serialize_bool: bool, serialize_i8: i8, serialize_i16: i16, serialize_i32: i32, serialize_i64: i64,
+        serialize_i128: i128, serialize_u8: u8, serialize_u16: u16, serialize_u32: u32, serialize_u64: u64,
+        serialize_u128: u128, serialize_f32: f32, serialize_f64: f64, serialize_char: char, serialize_str: &str
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), SerializeError> {
+        match std::str::from_utf8(value) {
+            Ok(text) => self.push(text.to_string()),
+            Err(_) => Err(self.record.unsupported("bytes that are not UTF-8")),
+        }
+    }
+
+    fn serialize_none(self) -> Result<(), SerializeError> {
+        self.push(String::new())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SerializeError> {
+        self.push(String::new())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), SerializeError> {
+        self.push(String::new())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<(), SerializeError> {
+        self.push(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<(), SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), SerializeError> {
+        Err(self.record.unsupported("an enum variant with data"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerializeError> {
+        Err(self.record.unsupported("a sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerializeError> {
+        Err(self.record.unsupported("a tuple"))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, SerializeError> {
+        Err(self.record.unsupported("a tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerializeError> {
+        Err(self.record.unsupported("an enum variant with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerializeError> {
+        Err(self.record.unsupported("a map"))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, SerializeError> {
+        match self.name {
+            Some(name) => Ok(StructFields { record: self.record, prefix: format!("{}_", name) }),
+            None => Err(self.record.unsupported("a struct in a sequence")),
+        }
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerializeError> {
+        Err(self.record.unsupported("an enum variant with data"))
+    }
+}
+
+/// Scalars make a record of one field, as `FieldSerializer` writes them.
+macro_rules! to_single_field {
+    ($($method:ident: $ty:ty),*) => {
+        $(fn $method(self, value: $ty) -> Result<(), SerializeError> {
+            FieldSerializer { record: self.record, name: None }.$method(value)
+        })*
+    };
+}
+
+impl<'a> ser::Serializer for RecordSerializer<'a> {
+    type Ok = ();
+    type Error = SerializeError;
+    type SerializeSeq = SeqFields<'a>;
+    type SerializeTuple = SeqFields<'a>;
+    type SerializeTupleStruct = SeqFields<'a>;
+    type SerializeTupleVariant = Impossible<(), SerializeError>;
+    type SerializeMap = Impossible<(), SerializeError>;
+    type SerializeStruct = StructFields<'a>;
+    type SerializeStructVariant = Impossible<(), SerializeError>;
+
+    to_single_field! {
+        serialize_bool: bool, serialize_i8: i8, serialize_i16: i16, serialize_i32: i32, serialize_i64: i64,
+        serialize_i128: i128, serialize_u8: u8, serialize_u16: u16, serialize_u32: u32, serialize_u64: u64,
+        serialize_u128: u128, serialize_f32: f32, serialize_f64: f64, serialize_char: char, serialize_str: &str,
+        serialize_bytes: &[u8]
+    }
+
+    fn serialize_none(self) -> Result<(), SerializeError> {
+        FieldSerializer { record: self.record, name: None }.serialize_none()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SerializeError> {
+        FieldSerializer { record: self.record, name: None }.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<(), SerializeError> {
+        FieldSerializer { record: self.record, name: None }.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(self, name: &'static str, index: u32, variant: &'static str) -> Result<(), SerializeError> {
+        FieldSerializer { record: self.record, name: None }.serialize_unit_variant(name, index, variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<(), SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), SerializeError> {
+        Err(self.record.unsupported("an enum variant with data"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerializeError> {
+        Ok(SeqFields { record: self.record })
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerializeError> {
+        Ok(SeqFields { record: self.record })
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, SerializeError> {
+        Ok(SeqFields { record: self.record })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerializeError> {
+        Err(self.record.unsupported("an enum variant with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerializeError> {
+        Err(self.record.unsupported("a map"))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, SerializeError> {
+        Ok(StructFields { record: self.record, prefix: String::new() })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerializeError> {
+        Err(self.record.unsupported("an enum variant with data"))
+    }
+}
+
+impl SerializeStruct for StructFields<'_> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), SerializeError> {
+        value.serialize(FieldSerializer { record: self.record, name: Some(format!("{}{}", self.prefix, key)) })
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl SerializeSeq for SeqFields<'_> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializeError> {
+        value.serialize(FieldSerializer { record: self.record, name: None })
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl SerializeTuple for SeqFields<'_> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializeError> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl SerializeTupleStruct for SeqFields<'_> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializeError> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
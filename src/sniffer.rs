@@ -0,0 +1,182 @@
+//! `Sniffer`, which guesses a `CsvConfig` from a sample of the input.
+
+use super::*;
+
+/// Guesses a `CsvConfig` from a sample of the input (typically the first few
+/// kilobytes), for tools that open arbitrary files.
+#[derive(Debug, Clone)]
+pub struct Sniffer {
+    delimiters: Vec<char>,
+    quotes: Vec<char>,
+}
+
+impl Default for Sniffer {
+    fn default() -> Self {
+        Sniffer { delimiters: vec![',', '\t', ';', '|', ':'], quotes: vec!['"', '\''] }
+    }
+}
+
+impl Sniffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Candidate delimiters, in order of preference when scores tie.
+    pub fn delimiters(mut self, delimiters: &[char]) -> Self {
+        self.delimiters = delimiters.to_vec();
+        self
+    }
+
+    /// Candidate quote characters, in order of preference.
+    pub fn quotes(mut self, quotes: &[char]) -> Self {
+        self.quotes = quotes.to_vec();
+        self
+    }
+
+    /// The most plausible dialect for `sample`. The delimiter is the candidate
+    /// that splits the sample's records into the most consistent number of
+    /// fields (more than one); the quote is the candidate seen most often at
+    /// field boundaries; the terminator is `CrLf` or `Lf` when the sample uses
+    /// only one kind of line ending. Anything undetectable keeps its default.
+    pub fn sniff(&self, sample: &str) -> CsvConfig {
+        let sample = complete_lines(sample);
+        let mut config = CsvConfig { terminator: sniff_terminator(sample), ..CsvConfig::default() };
+
+        let mut quote_score = 0;
+        for &quote in &self.quotes {
+            let score = self.delimiters.iter().map(|&delimiter| bounded_quotes(sample, delimiter, quote)).max().unwrap_or(0);
+            if score > quote_score {
+                (config.quote, config.escape, quote_score) = (Some(quote), Some(quote), score);
+            }
+        }
+
+        let mut best = None;
+        for &delimiter in &self.delimiters {
+            let candidate = CsvConfig { delimiter, ..config };
+            let Some(score) = field_count_consistency(sample, candidate) else { continue };
+            if best.as_ref().is_none_or(|&(_, best_score)| score > best_score) {
+                best = Some((delimiter, score));
+            }
+        }
+        if let Some((delimiter, _)) = best {
+            config.delimiter = delimiter;
+        }
+        config
+    }
+
+    /// Whether the first record of `sample` looks like a header row. Each
+    /// column whose data rows share a type (all integers, say) votes for a
+    /// header when the first row's value has a different type, and against it
+    /// when the type matches; text columns vote on consistent value lengths.
+    /// An empty or repeated name in the first row counts against a header.
+    pub fn has_header_row(&self, sample: &str) -> Confidence {
+        let config = self.sniff(sample);
+        let mut parser = CsvChunkParser::new(config);
+        let sample = complete_lines(sample);
+        let Ok(mut records) = parser.process_chunk(sample).map(|result| result.complete_rows) else {
+            return Confidence::Uncertain;
+        };
+        records.extend(parser.process_chunk("").map(|result| result.complete_rows).unwrap_or_default());
+        let Some((first, rows)) = records.split_first() else {
+            return Confidence::Uncertain;
+        };
+        if rows.is_empty() {
+            return Confidence::Uncertain;
+        }
+
+        let mut votes = 0i32;
+        let mut names = std::collections::HashSet::new();
+        for (column, name) in first.iter().enumerate() {
+            if name.trim().is_empty() || !names.insert(name) {
+                votes -= 1;
+                continue;
+            }
+            let values: Vec<&str> = rows.iter().filter_map(|row| row.get(column)).filter(|value| !value.is_empty()).collect();
+            let Some(kind) = values.first().map(|value| value_kind(value)) else { continue };
+            if !values.iter().all(|value| value_kind(value) == kind) {
+                continue;
+            }
+            if kind != FieldType::Text {
+                votes += if value_kind(name) == kind { -1 } else { 1 };
+            } else if values.iter().all(|value| value.chars().count() == values[0].chars().count()) {
+                votes += if name.chars().count() == values[0].chars().count() { -1 } else { 1 };
+            }
+        }
+        match votes {
+            1.. => Confidence::Likely,
+            0 => Confidence::Uncertain,
+            _ => Confidence::Unlikely,
+        }
+    }
+}
+
+/// Outcome of a `Sniffer` heuristic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confidence {
+    Likely,
+    Uncertain,
+    Unlikely,
+}
+
+impl Confidence {
+    pub fn is_likely(self) -> bool {
+        self == Confidence::Likely
+    }
+}
+
+/// The narrowest `FieldType` that accepts `value`.
+fn value_kind(value: &str) -> FieldType {
+    [FieldType::Integer, FieldType::Float, FieldType::Boolean]
+        .into_iter()
+        .find(|kind| kind.accepts(value))
+        .unwrap_or(FieldType::Text)
+}
+
+/// `sample` up to its last line break, dropping a final line that may have been cut off.
+fn complete_lines(sample: &str) -> &str {
+    match sample.rfind(['\n', '\r']) {
+        Some(end) if end + 1 < sample.len() => &sample[..end + 1],
+        _ => sample,
+    }
+}
+
+fn sniff_terminator(sample: &str) -> Terminator {
+    let crlf = sample.matches("\r\n").count();
+    let lf = sample.matches('\n').count() - crlf;
+    let cr = sample.matches('\r').count() - crlf;
+    match (crlf, lf, cr) {
+        (1.., 0, 0) => Terminator::CrLf,
+        (0, 1.., 0) => Terminator::Lf,
+        _ => Terminator::Any,
+    }
+}
+
+/// How often `quote` opens or closes a field when fields are split on `delimiter`.
+fn bounded_quotes(sample: &str, delimiter: char, quote: char) -> usize {
+    let is_boundary = |ch: Option<char>| ch.is_none_or(|ch| ch == delimiter || ch == '\n' || ch == '\r');
+    let chars: Vec<char> = sample.chars().collect();
+    (0..chars.len())
+        .filter(|&i| chars[i] == quote)
+        .filter(|&i| is_boundary(i.checked_sub(1).map(|p| chars[p])) || is_boundary(chars.get(i + 1).copied()))
+        .count()
+}
+
+/// Scores `config` by the share of records that have the most common field
+/// count, and that count. `None` if the sample does not parse or the
+/// delimiter never splits a record.
+fn field_count_consistency(sample: &str, config: CsvConfig) -> Option<(usize, usize)> {
+    let mut parser = CsvChunkParser::new(config);
+    let mut records = parser.process_chunk(sample).ok()?.complete_rows;
+    records.extend(parser.process_chunk("").ok()?.complete_rows);
+
+    let mut counts = std::collections::HashMap::new();
+    for record in &records {
+        *counts.entry(record.len()).or_insert(0usize) += 1;
+    }
+    let (fields, frequency) = counts.into_iter().max_by_key(|&(fields, frequency)| (frequency, fields))?;
+    if fields < 2 {
+        return None;
+    }
+    // Per-mille share, so scores compare exactly.
+    Some((frequency * 1000 / records.len(), fields))
+}
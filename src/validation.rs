@@ -0,0 +1,891 @@
+//! Whole-file checks: `validate`, `count_records`, the `Linter` and
+//! `check_rfc4180`, with the `ValidationReport` they produce.
+
+use super::*;
+use crate::reader::{DEFAULT_READ_CHUNK_SIZE, RecordCount};
+
+/// How serious a `ValidationIssue` is. RFC 4180 deviations that readers
+/// commonly accept are warnings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A kind of problem reported by `validate`, `Linter` or `check_rfc4180`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ValidationIssue {
+    /// A record whose field count differs from the header (or first record).
+    RaggedRow,
+    InvalidUtf8,
+    /// Data after a closing quote, a bare quote, a line break in a quoted field
+    /// (with `forbid_quoted_newlines`), or a quote left open at EOF.
+    QuoteError,
+    EmptyHeader,
+    DuplicateHeader,
+    /// A field or record over one of the `CsvConfig` size limits.
+    TooLarge,
+    /// A quoted field with spaces before its opening or after its closing quote.
+    SpaceAroundQuotes,
+    /// A `\r` not followed by `\n`.
+    BareCarriageReturn,
+    /// A record ended by `\n` alone rather than `\r\n`.
+    BareLineFeed,
+    /// No line break after the last record.
+    MissingFinalCrlf,
+    /// A field or header name ending in whitespace (`LintRule::TrailingWhitespace`).
+    TrailingWhitespace,
+    /// A header name cased unlike most of the others (`LintRule::HeaderCase`).
+    InconsistentHeaderCase,
+    /// A line break of a less common style than most (`LintRule::MixedTerminators`).
+    MixedTerminators,
+    /// Text in an otherwise numeric column (`LintRule::NumericColumns`).
+    NonNumericValue,
+}
+
+impl ValidationIssue {
+    fn of(err: &CsvError) -> Option<Self> {
+        Some(match err {
+            CsvError::UnequalLengths { .. } => ValidationIssue::RaggedRow,
+            CsvError::Utf8Error { .. } => ValidationIssue::InvalidUtf8,
+            CsvError::UnclosedQuote { .. }
+            | CsvError::DataAfterClosingQuote { .. }
+            | CsvError::BareQuote { .. }
+            | CsvError::NewlineInQuotedField { .. }
+            | CsvError::RunawayQuote { .. } => ValidationIssue::QuoteError,
+            CsvError::FieldTooLarge { .. }
+            | CsvError::TooManyFields { .. }
+            | CsvError::RecordTooLarge { .. }
+            | CsvError::MemoryBudgetExceeded { .. } => ValidationIssue::TooLarge,
+            _ => return None,
+        })
+    }
+
+    pub fn severity(self) -> Severity {
+        match self {
+            ValidationIssue::BareLineFeed
+            | ValidationIssue::MissingFinalCrlf
+            | ValidationIssue::TrailingWhitespace
+            | ValidationIssue::InconsistentHeaderCase
+            | ValidationIssue::MixedTerminators => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ValidationIssue::RaggedRow => "ragged rows",
+            ValidationIssue::InvalidUtf8 => "invalid UTF-8",
+            ValidationIssue::QuoteError => "quote errors",
+            ValidationIssue::EmptyHeader => "empty headers",
+            ValidationIssue::DuplicateHeader => "duplicate headers",
+            ValidationIssue::TooLarge => "oversized fields or records",
+            ValidationIssue::SpaceAroundQuotes => "spaces around quoted fields",
+            ValidationIssue::BareCarriageReturn => "bare carriage returns",
+            ValidationIssue::BareLineFeed => "records ended by a bare line feed",
+            ValidationIssue::MissingFinalCrlf => "missing line break at end of input",
+            ValidationIssue::TrailingWhitespace => "trailing whitespace",
+            ValidationIssue::InconsistentHeaderCase => "inconsistently cased headers",
+            ValidationIssue::MixedTerminators => "mixed line break styles",
+            ValidationIssue::NonNumericValue => "text in numeric columns",
+        })
+    }
+}
+
+/// One occurrence of a `ValidationIssue`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationSample {
+    /// Where it was found; for header issues, the start of the header row.
+    pub position: Position,
+    pub message: String,
+}
+
+/// Every occurrence of one issue in the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub issue: ValidationIssue,
+    /// `issue.severity()`, unless a `Linter` rule overrides it.
+    pub severity: Severity,
+    pub count: usize,
+    /// The first `CsvConfig::max_reported_errors` occurrences.
+    pub samples: Vec<ValidationSample>,
+}
+
+/// What `validate` found, in order of each issue's first occurrence.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    /// Data records read, including malformed ones.
+    pub records: usize,
+    pub findings: Vec<Finding>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Whether any finding is more than a `Severity::Warning`.
+    pub fn has_errors(&self) -> bool {
+        self.findings.iter().any(|finding| finding.severity == Severity::Error)
+    }
+
+    /// How many times `issue` occurred.
+    pub fn count(&self, issue: ValidationIssue) -> usize {
+        self.findings.iter().find(|finding| finding.issue == issue).map_or(0, |finding| finding.count)
+    }
+
+    fn finding(&mut self, issue: ValidationIssue, severity: Severity) -> &mut Finding {
+        let index = match self.findings.iter().position(|finding| finding.issue == issue) {
+            Some(index) => index,
+            None => {
+                self.findings.push(Finding { issue, severity, count: 0, samples: Vec::new() });
+                self.findings.len() - 1
+            }
+        };
+        &mut self.findings[index]
+    }
+
+    fn add(&mut self, issue: ValidationIssue, position: Position, message: String, max_samples: usize) {
+        self.add_as(issue, issue.severity(), position, message, max_samples);
+    }
+
+    fn add_as(&mut self, issue: ValidationIssue, severity: Severity, position: Position, message: String, max_samples: usize) {
+        let finding = self.finding(issue, severity);
+        finding.count += 1;
+        if finding.samples.len() < max_samples {
+            finding.samples.push(ValidationSample { position, message });
+        }
+    }
+
+    fn check_headers(&mut self, names: &[String], position: Position, max_samples: usize) {
+        let mut seen = std::collections::HashMap::with_capacity(names.len());
+        for (i, name) in names.iter().enumerate() {
+            if name.trim().is_empty() {
+                self.add(ValidationIssue::EmptyHeader, position, format!("column {} has no name", i), max_samples);
+            } else if let Some(first) = seen.insert(name.as_str(), i) {
+                seen.insert(name.as_str(), first);
+                let message = format!("header {:?} repeats column {} in column {}", name, first, i);
+                self.add(ValidationIssue::DuplicateHeader, position, message, max_samples);
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_valid() {
+            return write!(f, "{} records, no problems found", self.records);
+        }
+        write!(f, "{} records, {} kinds of problem found", self.records, self.findings.len())?;
+        for finding in &self.findings {
+            write!(f, "\n  {}: {}", finding.issue, finding.count)?;
+            if finding.severity == Severity::Warning {
+                f.write_str(" (warning)")?;
+            }
+            for sample in &finding.samples {
+                write!(f, "\n    line {}, column {}: {}", sample.position.line, sample.position.column, sample.message)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads all of `reader` and reports ragged rows, encoding problems, quote
+/// errors and empty or duplicate headers (with `has_headers`), instead of
+/// stopping at the first one. Parsing resumes after each malformed record as
+/// with `ErrorPolicy::Skip`; records are checked against the field count of
+/// the header or first record, whatever `strict_field_count` says. Fails only
+/// for I/O errors and invalid configuration.
+pub fn validate<R: std::io::Read>(reader: R, config: CsvConfig) -> Result<ValidationReport, CsvError> {
+    Linter::new(config).lint(reader)
+}
+
+/// Counts the data records in `reader`. Records go through the parser as
+/// for `CsvReader`, but are counted as they complete instead of being built,
+/// so counting is quicker than reading. Errors are handled as `CsvReader`
+/// would, by `error_policy`; `has_headers`, `skip_rows`, `skip_footer` and
+/// `max_records` are applied.
+pub fn count_records<R: std::io::Read>(reader: R, config: CsvConfig) -> Result<usize, CsvError> {
+    let mut reader = CsvReader::new(reader, config);
+    reader.count_only = Some(RecordCount::default());
+    while reader.next_record()?.is_some() {}
+    Ok(reader.count_only.map_or(0, |count| count.0))
+}
+
+/// The checks of `validate` without those of the header names: quote
+/// errors, invalid UTF-8, ragged rows and size limits, plus the number of
+/// data records. Records are counted as `count_records` does rather than
+/// built. Fails only for I/O errors and invalid configuration.
+pub fn validate_only<R: std::io::Read>(reader: R, config: CsvConfig) -> Result<ValidationReport, CsvError> {
+    let max_samples = config.max_reported_errors;
+    let reader_config = CsvConfig {
+        error_policy: ErrorPolicy::Skip,
+        max_reported_errors: usize::MAX,
+        strict_field_count: true,
+        duplicate_headers: DuplicateHeaderPolicy::KeepFirst,
+        unclosed_quote: UnclosedQuotePolicy::Error,
+        ..config
+    };
+    let mut reader = CsvReader::new(reader, reader_config);
+    reader.count_only = Some(RecordCount::default());
+    let mut report = ValidationReport::default();
+    loop {
+        let next = reader.next_record();
+        for error in reader.take_error_report().errors {
+            let issue = ValidationIssue::of(&error.error).ok_or_else(|| error.error.clone())?;
+            report.records += 1;
+            report.add(issue, error.position, error.error.to_string(), max_samples);
+        }
+        match next {
+            Ok(Some(_)) => {}
+            Ok(None) => break,
+            Err(err @ CsvError::UnclosedQuote { position, .. }) => {
+                report.add(ValidationIssue::QuoteError, position, err.to_string(), max_samples);
+                break;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    report.records += reader.count_only.map_or(0, |count| count.0);
+    Ok(report)
+}
+
+/// An optional check for `Linter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LintRule {
+    /// Fields and header names ending in spaces or tabs.
+    TrailingWhitespace,
+    /// Header names in a different style (`lower`, `UPPER`, `Title`) from most.
+    HeaderCase,
+    /// Line breaks in more than one style (`\r\n`, `\n`, `\r`). Positions
+    /// come from the raw bytes, so their `record_index` is always 0.
+    MixedTerminators,
+    /// Non-numeric text in a column whose values are at least 90% numbers
+    /// (see `Linter::with_number_format`).
+    NumericColumns,
+}
+
+impl LintRule {
+    /// The issue this rule reports.
+    pub fn issue(self) -> ValidationIssue {
+        match self {
+            LintRule::TrailingWhitespace => ValidationIssue::TrailingWhitespace,
+            LintRule::HeaderCase => ValidationIssue::InconsistentHeaderCase,
+            LintRule::MixedTerminators => ValidationIssue::MixedTerminators,
+            LintRule::NumericColumns => ValidationIssue::NonNumericValue,
+        }
+    }
+}
+
+/// `validate` with extra, selectable rules. Streams the input once and
+/// reports everything in one `ValidationReport`.
+#[derive(Debug, Clone)]
+pub struct Linter {
+    config: CsvConfig,
+    rules: Vec<(LintRule, Severity)>,
+    number_format: NumberFormat,
+}
+
+impl Linter {
+    /// A linter with no rules beyond `validate`'s checks.
+    pub fn new(config: CsvConfig) -> Self {
+        Linter { config, rules: Vec::new(), number_format: NumberFormat::new('.', Some(',')) }
+    }
+
+    /// Enables `rule` at its issue's default severity.
+    pub fn with_rule(self, rule: LintRule) -> Self {
+        self.with_rule_severity(rule, rule.issue().severity())
+    }
+
+    pub fn with_rule_severity(mut self, rule: LintRule, severity: Severity) -> Self {
+        self.rules.retain(|&(enabled, _)| enabled != rule);
+        self.rules.push((rule, severity));
+        self
+    }
+
+    /// How `LintRule::NumericColumns` recognizes numbers; `1,234.5` by default.
+    pub fn with_number_format(mut self, format: NumberFormat) -> Self {
+        self.number_format = format;
+        self
+    }
+
+    fn severity(&self, rule: LintRule) -> Option<Severity> {
+        self.rules.iter().find(|&&(enabled, _)| enabled == rule).map(|&(_, severity)| severity)
+    }
+
+    pub fn lint<R: std::io::Read>(&self, reader: R) -> Result<ValidationReport, CsvError> {
+        let config = self.config;
+        let whitespace = self.severity(LintRule::TrailingWhitespace);
+        let numeric = self.severity(LintRule::NumericColumns);
+        let field_rules = whitespace.is_some() || numeric.is_some();
+        let terminators = self.severity(LintRule::MixedTerminators).is_some();
+        let reader_config = CsvConfig {
+            error_policy: ErrorPolicy::Skip,
+            max_reported_errors: usize::MAX,
+            strict_field_count: true,
+            duplicate_headers: DuplicateHeaderPolicy::KeepFirst,
+            unclosed_quote: UnclosedQuotePolicy::Error,
+            // Field positions are worked out from the spans and raw text, and
+            // line breaks in quoted fields from each record's raw text.
+            track_field_spans: config.track_field_spans || field_rules,
+            retain_raw_records: config.retain_raw_records || field_rules || terminators,
+            ..config
+        };
+        let max_samples = config.max_reported_errors;
+        let scan = TerminatorScan::new(reader, terminators, max_samples);
+        let mut reader = CsvReader::new(scan, reader_config);
+        let mut report = ValidationReport::default();
+        let mut columns: Vec<ColumnStats> = Vec::new();
+        let mut headers_checked = !config.has_headers;
+        loop {
+            let next = reader.next_record();
+            if !headers_checked && let Some(names) = reader.parser.headers() {
+                let position = reader.parser.header_position;
+                report.check_headers(names, position, max_samples);
+                if let Some(severity) = whitespace {
+                    for (i, name) in names.iter().enumerate().filter(|(_, name)| name.ends_with([' ', '\t'])) {
+                        let message = format!("header {:?} of column {} ends with whitespace", name, i);
+                        report.add_as(ValidationIssue::TrailingWhitespace, severity, position, message, max_samples);
+                    }
+                }
+                if let Some(severity) = self.severity(LintRule::HeaderCase) {
+                    check_header_case(&mut report, names, position, severity, max_samples);
+                }
+                headers_checked = true;
+            }
+            for error in reader.take_error_report().errors {
+                let issue = ValidationIssue::of(&error.error).ok_or_else(|| error.error.clone())?;
+                report.records += 1;
+                report.add(issue, error.position, error.error.to_string(), max_samples);
+            }
+            match next {
+                Ok(Some(record)) => {
+                    let start = record.byte_offset();
+                    reader.inner.record(start..start + record.raw().map_or(0, str::len));
+                    report.records += 1;
+                    if let Some(severity) = whitespace {
+                        for (i, field) in record.iter().enumerate().filter(|(_, field)| field.ends_with([' ', '\t'])) {
+                            let message = format!("field {} ends with whitespace: {:?}", i, field);
+                            let position = field_position(&record, i);
+                            report.add_as(ValidationIssue::TrailingWhitespace, severity, position, message, max_samples);
+                        }
+                    }
+                    if numeric.is_some() {
+                        columns.resize_with(columns.len().max(record.len()), ColumnStats::default);
+                        for (i, column) in columns.iter_mut().enumerate().take(record.len()) {
+                            column.observe(&record, i, &self.number_format, max_samples);
+                        }
+                    }
+                }
+                Ok(None) => break,
+                // Nothing after a quote left open at EOF can be checked.
+                Err(err @ CsvError::UnclosedQuote { position, .. }) => {
+                    report.add(ValidationIssue::QuoteError, position, err.to_string(), max_samples);
+                    break;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        if let Some(severity) = numeric {
+            for column in columns {
+                column.report(&mut report, severity, max_samples);
+            }
+        }
+        if let Some(severity) = self.severity(LintRule::MixedTerminators) {
+            reader.inner.report(&mut report, severity);
+        }
+        Ok(report)
+    }
+}
+
+/// Where field `i` of `record` starts, from its span and the record's raw text.
+fn field_position(record: &StringRecord, i: usize) -> Position {
+    let start = record.field_span(i).map_or(record.byte_offset(), |span| span.start);
+    let before = record.raw().and_then(|raw| raw.get(..start - record.byte_offset())).unwrap_or_default();
+    let (line, column) = match before.rfind('\n') {
+        Some(newline) => (record.line() + before.matches('\n').count(), before.len() - newline),
+        None => (record.line(), record.column.max(1) + before.len()),
+    };
+    Position { line, column, byte_offset: start, record_index: record.record_index() }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HeaderCase {
+    Lower,
+    Upper,
+    Title,
+}
+
+impl HeaderCase {
+    /// The style of `name`, if it has at least two letters to tell by.
+    fn of(name: &str) -> Option<Self> {
+        let mut letters = name.chars().filter(|ch| ch.is_alphabetic());
+        let first = letters.next()?;
+        let rest: Vec<char> = letters.collect();
+        if rest.is_empty() {
+            return None;
+        }
+        Some(if first.is_lowercase() && rest.iter().all(|ch| ch.is_lowercase()) {
+            HeaderCase::Lower
+        } else if first.is_uppercase() && rest.iter().all(|ch| ch.is_uppercase()) {
+            HeaderCase::Upper
+        } else {
+            // Mixed case, including camelCase, which capitalizes words too.
+            HeaderCase::Title
+        })
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            HeaderCase::Lower => "lower case",
+            HeaderCase::Upper => "UPPER CASE",
+            HeaderCase::Title => "Title Case",
+        }
+    }
+}
+
+fn check_header_case(report: &mut ValidationReport, names: &[String], position: Position, severity: Severity, max_samples: usize) {
+    let styles: Vec<Option<HeaderCase>> = names.iter().map(|name| HeaderCase::of(name)).collect();
+    let count = |style: HeaderCase| styles.iter().filter(|&&s| s == Some(style)).count();
+    let Some(usual) = [HeaderCase::Lower, HeaderCase::Upper, HeaderCase::Title]
+        .into_iter()
+        .max_by_key(|&style| count(style))
+        .filter(|&style| count(style) > 0)
+    else {
+        return;
+    };
+    for (i, style) in styles.iter().enumerate() {
+        if let Some(style) = style
+            && *style != usual
+        {
+            let message =
+                format!("header {:?} of column {} is {}; most are {}", names[i], i, style.describe(), usual.describe());
+            report.add_as(ValidationIssue::InconsistentHeaderCase, severity, position, message, max_samples);
+        }
+    }
+}
+
+/// Numbers and text seen in one column, for `LintRule::NumericColumns`.
+#[derive(Debug, Default)]
+struct ColumnStats {
+    numbers: usize,
+    texts: usize,
+    samples: Vec<ValidationSample>,
+}
+
+impl ColumnStats {
+    fn observe(&mut self, record: &StringRecord, i: usize, format: &NumberFormat, max_samples: usize) {
+        let Some(Some(value)) = record.get_opt(i) else { return };
+        if value.trim().is_empty() {
+            return;
+        }
+        if format.normalize(value).is_some() {
+            self.numbers += 1;
+            return;
+        }
+        self.texts += 1;
+        if self.samples.len() < max_samples {
+            let column = match record.headers().and_then(|names| names.get(i)) {
+                Some(name) => format!("{:?}", name),
+                None => i.to_string(),
+            };
+            let message = format!("{:?} in numeric column {}", value, column);
+            self.samples.push(ValidationSample { position: field_position(record, i), message });
+        }
+    }
+
+    /// Reports the text if the column is mostly numbers.
+    fn report(self, report: &mut ValidationReport, severity: Severity, max_samples: usize) {
+        if self.texts == 0 || self.texts * 10 > self.numbers + self.texts {
+            return;
+        }
+        let finding = report.finding(ValidationIssue::NonNumericValue, severity);
+        finding.count += self.texts;
+        let room = max_samples.saturating_sub(finding.samples.len());
+        finding.samples.extend(self.samples.into_iter().take(room));
+    }
+}
+
+/// Passes bytes through, counting line breaks by style (`\r\n`, `\n`, `\r`)
+/// for `LintRule::MixedTerminators`. Breaks wait in `pending` until the
+/// parser has placed them: those inside a record's text (in quoted fields)
+/// are dropped, the rest end records or blank lines.
+struct TerminatorScan<R> {
+    inner: R,
+    enabled: bool,
+    max_samples: usize,
+    offset: usize,
+    line: usize,
+    line_start: usize,
+    pending_cr: Option<Position>,
+    pending: std::collections::VecDeque<(usize, Position)>,
+    /// Count and first positions of each style, in the order above.
+    styles: [(usize, Vec<Position>); 3],
+}
+
+impl<R> TerminatorScan<R> {
+    const NAMES: [&'static str; 3] = ["\\r\\n", "\\n", "\\r"];
+
+    fn new(inner: R, enabled: bool, max_samples: usize) -> Self {
+        TerminatorScan {
+            inner,
+            enabled,
+            max_samples,
+            offset: 0,
+            line: 1,
+            line_start: 0,
+            pending_cr: None,
+            pending: Default::default(),
+            styles: Default::default(),
+        }
+    }
+
+    fn count(&mut self, style: usize, position: Position) {
+        let (count, samples) = &mut self.styles[style];
+        *count += 1;
+        if samples.len() < self.max_samples {
+            samples.push(position);
+        }
+    }
+
+    /// Places the pending breaks up to the end of a record spanning `span`.
+    fn record(&mut self, span: std::ops::Range<usize>) {
+        while let Some(&(style, position)) = self.pending.front()
+            && position.byte_offset < span.end
+        {
+            self.pending.pop_front();
+            if position.byte_offset < span.start {
+                self.count(style, position);
+            }
+        }
+    }
+
+    fn byte(&mut self, b: u8) {
+        let position = Position {
+            line: self.line,
+            column: self.offset - self.line_start + 1,
+            byte_offset: self.offset,
+            record_index: 0,
+        };
+        match (self.pending_cr.take(), b) {
+            (Some(cr), b'\n') => self.pending.push_back((0, cr)),
+            (Some(cr), _) => {
+                self.pending.push_back((2, cr));
+                if b == b'\r' {
+                    self.pending_cr = Some(position);
+                }
+            }
+            (None, b'\n') => self.pending.push_back((1, position)),
+            (None, b'\r') => self.pending_cr = Some(position),
+            (None, _) => {}
+        }
+        if b == b'\n' {
+            self.line += 1;
+            self.line_start = self.offset + 1;
+        }
+        self.offset += 1;
+    }
+
+    fn report(&mut self, report: &mut ValidationReport, severity: Severity) {
+        if let Some(cr) = self.pending_cr.take() {
+            self.pending.push_back((2, cr));
+        }
+        for (style, position) in std::mem::take(&mut self.pending) {
+            self.count(style, position);
+        }
+        if self.styles.iter().filter(|(count, _)| *count > 0).count() < 2 {
+            return;
+        }
+        let usual = (0..3).max_by_key(|&style| self.styles[style].0).unwrap_or_default();
+        for (style, (count, samples)) in self.styles.iter().enumerate().filter(|&(style, _)| style != usual) {
+            let finding = report.finding(ValidationIssue::MixedTerminators, severity);
+            finding.count += count;
+            for &position in samples.iter().take(self.max_samples.saturating_sub(finding.samples.len())) {
+                let message = format!("line break is {}; most are {}", Self::NAMES[style], Self::NAMES[usual]);
+                finding.samples.push(ValidationSample { position, message });
+            }
+        }
+    }
+}
+
+impl<R: std::io::Read> std::io::Read for TerminatorScan<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let bytes_read = self.inner.read(buf)?;
+        if self.enabled {
+            for &b in &buf[..bytes_read] {
+                self.byte(b);
+            }
+        }
+        Ok(bytes_read)
+    }
+}
+
+/// Where `Rfc4180Checker` is within a field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Rfc4180State {
+    FieldStart,
+    /// `blank` while the field is only spaces so far.
+    Unquoted { blank: bool },
+    Quoted,
+    QuoteSeen,
+    /// Spaces after a closing quote, the first at byte `space`.
+    AfterQuote { space: usize },
+}
+
+/// Byte-level RFC 4180 scanner behind `check_rfc4180`. It works on raw bytes,
+/// since the parser accepts (and so hides) the deviations it looks for.
+struct Rfc4180Checker {
+    state: Rfc4180State,
+    offset: usize,
+    line: usize,
+    line_start: usize,
+    record_index: usize,
+    /// Where the current record started, once it has.
+    record_start: Option<Position>,
+    fields: usize,
+    expected_fields: Option<usize>,
+    quote_open: Position,
+    /// A `\r` ending a record, until the next byte shows whether it is `\r\n`.
+    pending_cr: Option<Position>,
+    max_samples: usize,
+    report: ValidationReport,
+}
+
+impl Rfc4180Checker {
+    fn new(max_samples: usize) -> Self {
+        Rfc4180Checker {
+            state: Rfc4180State::FieldStart,
+            offset: 0,
+            line: 1,
+            line_start: 0,
+            record_index: 0,
+            record_start: None,
+            fields: 0,
+            expected_fields: None,
+            quote_open: Position::default(),
+            pending_cr: None,
+            max_samples,
+            report: ValidationReport::default(),
+        }
+    }
+
+    fn position_at(&self, byte_offset: usize) -> Position {
+        Position {
+            line: self.line,
+            column: byte_offset - self.line_start + 1,
+            byte_offset,
+            record_index: self.record_index,
+        }
+    }
+
+    fn add(&mut self, issue: ValidationIssue, position: Position, message: impl Into<String>) {
+        self.report.add(issue, position, message.into(), self.max_samples);
+    }
+
+    fn feed(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.byte(b);
+            self.offset += 1;
+        }
+    }
+
+    fn byte(&mut self, b: u8) {
+        let at = self.offset;
+        if let Some(cr) = self.pending_cr.take() {
+            if b == b'\n' {
+                self.line += 1;
+                self.line_start = at + 1;
+                return;
+            }
+            self.add(ValidationIssue::BareCarriageReturn, cr, "line break is a lone \\r, expected \\r\\n");
+        }
+        if self.record_start.is_none() {
+            self.record_start = Some(self.position_at(at));
+        }
+        match (self.state, b) {
+            (Rfc4180State::Quoted, b'"') => self.state = Rfc4180State::QuoteSeen,
+            (Rfc4180State::Quoted, b'\n') => {
+                self.line += 1;
+                self.line_start = at + 1;
+            }
+            (Rfc4180State::Quoted, _) => {}
+            (Rfc4180State::QuoteSeen, b'"') => self.state = Rfc4180State::Quoted,
+            (Rfc4180State::QuoteSeen, b' ') => self.state = Rfc4180State::AfterQuote { space: at },
+            (Rfc4180State::AfterQuote { .. }, b' ') => {}
+            (Rfc4180State::QuoteSeen | Rfc4180State::AfterQuote { .. }, b',' | b'\r' | b'\n') => {
+                if let Rfc4180State::AfterQuote { space } = self.state {
+                    self.add(ValidationIssue::SpaceAroundQuotes, self.position_at(space), "space after a closing quote");
+                }
+                self.end_field(b, at);
+            }
+            (Rfc4180State::QuoteSeen | Rfc4180State::AfterQuote { .. }, _) => {
+                let message = format!("unexpected {:?} after a closing quote", b as char);
+                self.add(ValidationIssue::QuoteError, self.position_at(at), message);
+                self.state = Rfc4180State::Unquoted { blank: false };
+            }
+            (Rfc4180State::FieldStart | Rfc4180State::Unquoted { .. }, b',' | b'\r' | b'\n') => self.end_field(b, at),
+            (Rfc4180State::FieldStart, b'"') => {
+                self.quote_open = self.position_at(at);
+                self.state = Rfc4180State::Quoted;
+            }
+            (Rfc4180State::Unquoted { blank: true }, b'"') => {
+                self.quote_open = self.position_at(at);
+                self.add(ValidationIssue::SpaceAroundQuotes, self.quote_open, "space before an opening quote");
+                self.state = Rfc4180State::Quoted;
+            }
+            (Rfc4180State::Unquoted { .. }, b'"') => {
+                self.add(ValidationIssue::QuoteError, self.position_at(at), "quote inside an unquoted field");
+            }
+            (Rfc4180State::FieldStart | Rfc4180State::Unquoted { blank: true }, b' ') => {
+                self.state = Rfc4180State::Unquoted { blank: true };
+            }
+            (Rfc4180State::FieldStart | Rfc4180State::Unquoted { .. }, _) => {
+                self.state = Rfc4180State::Unquoted { blank: false };
+            }
+        }
+    }
+
+    /// Ends a field at the delimiter or line break `b`.
+    fn end_field(&mut self, b: u8, at: usize) {
+        self.fields += 1;
+        self.state = Rfc4180State::FieldStart;
+        match b {
+            b',' => {}
+            b'\r' => {
+                self.pending_cr = Some(self.position_at(at));
+                self.end_record(at);
+            }
+            _ => {
+                self.add(ValidationIssue::BareLineFeed, self.position_at(at), "record ends with \\n, expected \\r\\n");
+                self.end_record(at);
+                self.line += 1;
+                self.line_start = at + 1;
+            }
+        }
+    }
+
+    /// Ends a record whose line break (if any) is at `at`. Blank lines are not records.
+    fn end_record(&mut self, at: usize) {
+        let fields = std::mem::take(&mut self.fields);
+        let Some(start) = self.record_start.take() else { return };
+        if fields == 1 && start.byte_offset == at {
+            return;
+        }
+        let expected = *self.expected_fields.get_or_insert(fields);
+        if fields != expected {
+            let message = format!("record has {} fields, expected {}", fields, expected);
+            self.add(ValidationIssue::RaggedRow, start, message);
+        }
+        self.record_index += 1;
+        self.report.records += 1;
+    }
+
+    fn finish(mut self) -> ValidationReport {
+        if let Some(cr) = self.pending_cr.take() {
+            self.add(ValidationIssue::BareCarriageReturn, cr, "line break is a lone \\r, expected \\r\\n");
+        } else if self.state == Rfc4180State::Quoted {
+            self.add(ValidationIssue::QuoteError, self.quote_open, "quote is never closed");
+        } else if self.record_start.is_some() {
+            if let Rfc4180State::AfterQuote { space } = self.state {
+                self.add(ValidationIssue::SpaceAroundQuotes, self.position_at(space), "space after a closing quote");
+            }
+            let position = self.position_at(self.offset);
+            self.add(ValidationIssue::MissingFinalCrlf, position, "no line break after the last record");
+            self.fields += 1;
+            self.end_record(self.offset);
+        }
+        self.report
+    }
+}
+
+/// Checks `reader` against RFC 4180, reporting every deviation: line breaks
+/// other than `\r\n`, quotes inside unquoted fields, spaces around quoted
+/// fields, data after a closing quote, unclosed quotes, records with differing
+/// field counts, and no line break after the last record. A bare `\n` and the
+/// missing final line break are only warnings; `ValidationReport::has_errors`
+/// tells whether a file can be certified.
+pub fn check_rfc4180<R: std::io::Read>(mut reader: R) -> Result<ValidationReport, CsvError> {
+    let mut checker = Rfc4180Checker::new(CsvConfig::default().max_reported_errors);
+    let mut buf = vec![0u8; DEFAULT_READ_CHUNK_SIZE];
+    loop {
+        let bytes_read = reader.read(&mut buf)?;
+        if bytes_read == 0 {
+            return Ok(checker.finish());
+        }
+        checker.feed(&buf[..bytes_read]);
+    }
+}
+
+/// Decimal and digit-grouping separators of a numeric column, for turning
+/// locale-formatted numbers (`1.234,56`) into canonical ones (`1234.56`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub decimal: char,
+    /// Thousands separator. Any space (including no-break spaces) stands for
+    /// all of them.
+    pub grouping: Option<char>,
+}
+
+impl NumberFormat {
+    pub fn new(decimal: char, grouping: Option<char>) -> Self {
+        NumberFormat { decimal, grouping }
+    }
+
+    /// `1.234,56`, as used in most of continental Europe and Latin America.
+    pub fn decimal_comma() -> Self {
+        NumberFormat::new(',', Some('.'))
+    }
+
+    /// The separators for a BCP 47 / POSIX locale such as `de-DE`, `fr_FR` or
+    /// `en`. `None` for locales not in the built-in table.
+    pub fn locale(tag: &str) -> Option<Self> {
+        let tag = tag.replace('_', "-").to_ascii_lowercase();
+        if tag == "de-ch" || tag == "it-ch" {
+            return Some(NumberFormat::new('.', Some('\'')));
+        }
+        let language = tag.split('-').next().unwrap_or_default();
+        let format = match language {
+            "en" | "ja" | "zh" | "ko" | "he" | "th" | "hi" | "ms" => NumberFormat::new('.', Some(',')),
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" => NumberFormat::decimal_comma(),
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "uk" | "hu" | "bg" => {
+                NumberFormat::new(',', Some('\u{a0}'))
+            }
+            _ => return None,
+        };
+        Some(format)
+    }
+
+    /// `value` in canonical form (`-1234.56`), or `None` if it is not a number
+    /// in this format. Surrounding whitespace is ignored.
+    pub fn normalize(&self, value: &str) -> Option<String> {
+        let is_space = |ch: char| ch == ' ' || ch == '\u{a0}' || ch == '\u{202f}';
+        let is_grouping = |ch: char| match self.grouping {
+            Some(grouping) if is_space(grouping) => is_space(ch),
+            Some(grouping) => ch == grouping,
+            None => false,
+        };
+        let value = value.trim();
+        let mut canonical = String::with_capacity(value.len());
+        let mut seen_decimal = false;
+        let mut prev_digit = false;
+        for (i, ch) in value.chars().enumerate() {
+            match ch {
+                '0'..='9' => canonical.push(ch),
+                '-' | '+' if i == 0 => canonical.push(ch),
+                ch if ch == self.decimal && !seen_decimal => {
+                    seen_decimal = true;
+                    canonical.push('.');
+                }
+                // Grouping separators only sit between digits of the integer part.
+                ch if is_grouping(ch) && prev_digit && !seen_decimal => {}
+                _ => return None,
+            }
+            prev_digit = ch.is_ascii_digit();
+        }
+        canonical.chars().any(|ch| ch.is_ascii_digit()).then_some(canonical)
+    }
+}
@@ -0,0 +1,708 @@
+//! Writing records as CSV: `CsvWriter` with its column formats and
+//! `WriteSchema` checks, `to_csv_string`, and `CsvRewriter` for converting dialects.
+
+use super::*;
+use crate::reader::DEFAULT_READ_CHUNK_SIZE;
+
+/// Records are encoded into an internal buffer and handed to the underlying
+/// writer once this many bytes have accumulated.
+const WRITER_FLUSH_THRESHOLD: usize = 64 * 1024;
+
+/// Number of records each worker encodes per task in `par_write_all`.
+#[cfg(feature = "parallel")]
+const PARALLEL_ENCODE_BATCH: usize = 1024;
+
+#[inline]
+fn needs_quoting(field: &str, config: &CsvConfig, quote: char) -> bool {
+    field.chars().any(|ch| {
+        ch == config.delimiter || ch == quote || Some(ch) == config.escape || ch == '\n' || ch == '\r'
+    })
+}
+
+/// Encodes one field, quoting only when the content would otherwise be
+/// ambiguous to `CsvChunkParser` under the same config. Without a quote
+/// character the field is written as is; without an escape character, quotes
+/// inside a quoted field are written as is (the dialect cannot represent them).
+pub(crate) fn encode_field(field: &str, config: &CsvConfig, force_quote: bool, out: &mut Vec<u8>) {
+    if config.escape_sequences.has_null_marker()
+        && let Some(escape) = config.escape
+    {
+        encode_escaped_field(field, config, escape, force_quote, out);
+        return;
+    }
+    let sequences;
+    let field = if config.escape_sequences == EscapeSequences::Backslash {
+        sequences = encode_backslash_sequences(field);
+        sequences.as_ref()
+    } else {
+        field
+    };
+    let Some(quote) = config.quote.filter(|&quote| force_quote || needs_quoting(field, config, quote)) else {
+        out.extend_from_slice(field.as_bytes());
+        return;
+    };
+
+    let mut utf8_buf = [0u8; 4];
+    out.extend_from_slice(quote.encode_utf8(&mut utf8_buf).as_bytes());
+    for ch in field.chars() {
+        // RFC 4180 doubles the quote; a custom escape prefixes both quote and escape.
+        if let Some(escape) = config.escape
+            && (ch == quote || ch == escape)
+        {
+            out.extend_from_slice(escape.encode_utf8(&mut utf8_buf).as_bytes());
+        }
+        out.extend_from_slice(ch.encode_utf8(&mut utf8_buf).as_bytes());
+    }
+    out.extend_from_slice(quote.encode_utf8(&mut utf8_buf).as_bytes());
+}
+
+/// Rewrites line breaks, tabs and backslashes as `EscapeSequences::Backslash`
+/// sequences; quoting is then applied as usual.
+fn encode_backslash_sequences(field: &str) -> std::borrow::Cow<'_, str> {
+    if !field.contains(['\\', '\n', '\r', '\t']) {
+        return std::borrow::Cow::Borrowed(field);
+    }
+    let mut encoded = String::with_capacity(field.len() + 8);
+    for ch in field.chars() {
+        match EscapeSequences::Backslash.encode(ch) {
+            Some(letter) => {
+                encoded.push('\\');
+                encoded.push(letter);
+            }
+            None => encoded.push(ch),
+        }
+    }
+    std::borrow::Cow::Owned(encoded)
+}
+
+/// Encodes one field of an escape-sequence dialect: null tokens become the null
+/// marker, and the escape character, quote, delimiter and line breaks are
+/// escaped, so quoting is never needed (but is honoured when forced).
+fn encode_escaped_field(field: &str, config: &CsvConfig, escape: char, force_quote: bool, out: &mut Vec<u8>) {
+    let mut utf8_buf = [0u8; 4];
+    let escape_bytes = escape.encode_utf8(&mut utf8_buf).as_bytes().to_vec();
+    if config.null_tokens.contains(&field) {
+        out.extend_from_slice(&escape_bytes);
+        out.push(b'N');
+        return;
+    }
+    let quote = config.quote.filter(|_| force_quote);
+    if let Some(quote) = quote {
+        out.extend_from_slice(quote.encode_utf8(&mut utf8_buf).as_bytes());
+    }
+    for ch in field.chars() {
+        if let Some(letter) = config.escape_sequences.encode(ch) {
+            out.extend_from_slice(&escape_bytes);
+            out.push(letter as u8);
+            continue;
+        }
+        if ch == escape || Some(ch) == config.quote || ch == config.delimiter || ch == '\n' || ch == '\r' {
+            out.extend_from_slice(&escape_bytes);
+        }
+        out.extend_from_slice(ch.encode_utf8(&mut utf8_buf).as_bytes());
+    }
+    if let Some(quote) = quote {
+        out.extend_from_slice(quote.encode_utf8(&mut utf8_buf).as_bytes());
+    }
+}
+
+/// Formatting callback: receives the original field and appends the formatted text.
+pub type FieldFormatFn = std::sync::Arc<dyn Fn(&str, &mut String) + Send + Sync>;
+
+/// A per-column transformation applied to field text while it is being written.
+#[derive(Clone)]
+pub enum ColumnFormat {
+    /// Re-render numeric fields with a fixed number of decimal places.
+    /// Fields that don't parse as numbers are written unchanged.
+    FixedPrecision(usize),
+    /// Right-align within `width` characters using `fill`.
+    PadLeft { width: usize, fill: char },
+    /// Left-align within `width` characters using `fill`.
+    PadRight { width: usize, fill: char },
+    /// Arbitrary formatting (e.g. date reformatting) that writes into the provided buffer.
+    Custom(FieldFormatFn),
+}
+
+impl ColumnFormat {
+    fn apply(&self, field: &str, out: &mut String) {
+        use std::fmt::Write;
+
+        match self {
+            ColumnFormat::FixedPrecision(precision) => match field.trim().parse::<f64>() {
+                Ok(value) => {
+                    let _ = write!(out, "{:.*}", precision, value);
+                }
+                Err(_) => out.push_str(field),
+            },
+            ColumnFormat::PadLeft { width, fill } => {
+                let len = field.chars().count();
+                out.extend(std::iter::repeat_n(*fill, width.saturating_sub(len)));
+                out.push_str(field);
+            }
+            ColumnFormat::PadRight { width, fill } => {
+                let len = field.chars().count();
+                out.push_str(field);
+                out.extend(std::iter::repeat_n(*fill, width.saturating_sub(len)));
+            }
+            ColumnFormat::Custom(format) => format(field, out),
+        }
+    }
+}
+
+/// Expected content type of a column in a `WriteSchema`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    Text,
+    Integer,
+    Float,
+    Boolean,
+}
+
+impl FieldType {
+    pub(crate) fn accepts(self, field: &str) -> bool {
+        match self {
+            FieldType::Text => true,
+            FieldType::Integer => field.parse::<i64>().is_ok(),
+            FieldType::Float => field.parse::<f64>().is_ok(),
+            FieldType::Boolean => field.eq_ignore_ascii_case("true") || field.eq_ignore_ascii_case("false"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ColumnSchema {
+    pub name: String,
+    pub field_type: FieldType,
+    /// Whether an empty field, or one of the writer's `null_tokens`, is allowed.
+    pub nullable: bool,
+}
+
+/// Column layout that every record written through a `CsvWriter` must match.
+#[derive(Debug, Clone, Default)]
+pub struct WriteSchema {
+    columns: Vec<ColumnSchema>,
+}
+
+impl WriteSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn column(mut self, name: &str, field_type: FieldType) -> Self {
+        self.columns.push(ColumnSchema { name: name.to_string(), field_type, nullable: false });
+        self
+    }
+
+    pub fn nullable_column(mut self, name: &str, field_type: FieldType) -> Self {
+        self.columns.push(ColumnSchema { name: name.to_string(), field_type, nullable: true });
+        self
+    }
+
+    pub fn columns(&self) -> &[ColumnSchema] {
+        &self.columns
+    }
+
+    /// Checks one field against its column. Empty fields and the writer
+    /// config's `null_tokens` are nulls: accepted without a type check in a
+    /// nullable column, rejected in any other.
+    fn check_field(&self, record_index: usize, column: usize, field: &str, null_tokens: &[&str]) -> Result<(), CsvError> {
+        let Some(schema) = self.columns.get(column) else {
+            return Err(CsvError::SchemaViolation {
+                record_index,
+                message: format!("expected {} fields, found more", self.columns.len()),
+            });
+        };
+
+        if field.is_empty() || null_tokens.contains(&field) {
+            if schema.nullable {
+                return Ok(());
+            }
+            let message = if field.is_empty() {
+                format!("column '{}' is not nullable but the field is empty", schema.name)
+            } else {
+                format!("column '{}' is not nullable but the field is the null token {:?}", schema.name, field)
+            };
+            return Err(CsvError::SchemaViolation { record_index, message });
+        }
+
+        if !schema.field_type.accepts(field) {
+            return Err(CsvError::SchemaViolation {
+                record_index,
+                message: format!("column '{}' expects {:?}, got {:?}", schema.name, schema.field_type, field),
+            });
+        }
+        Ok(())
+    }
+
+    fn check_field_count(&self, record_index: usize, field_count: usize) -> Result<(), CsvError> {
+        if field_count != self.columns.len() {
+            return Err(CsvError::SchemaViolation {
+                record_index,
+                message: format!("expected {} fields, found {}", self.columns.len(), field_count),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Holds everything needed to turn a record into bytes. Cloned per worker for
+/// parallel encoding so each one gets its own scratch buffer.
+#[derive(Clone)]
+struct RecordEncoder {
+    config: CsvConfig,
+    formats: Vec<Option<ColumnFormat>>,
+    forced_quotes: Vec<bool>,
+    /// Column names to force-quote, resolved against the first record written.
+    pending_quote_names: Vec<String>,
+    schema: Option<WriteSchema>,
+    /// Index of the next data record, used in error reports.
+    record_index: usize,
+    scratch: String,
+}
+
+impl RecordEncoder {
+    fn new(config: CsvConfig) -> Self {
+        Self {
+            config,
+            formats: Vec::new(),
+            forced_quotes: Vec::new(),
+            pending_quote_names: Vec::new(),
+            schema: None,
+            record_index: 0,
+            scratch: String::with_capacity(64),
+        }
+    }
+
+    fn force_quote(&mut self, index: usize) {
+        if self.forced_quotes.len() <= index {
+            self.forced_quotes.resize(index + 1, false);
+        }
+        self.forced_quotes[index] = true;
+    }
+
+    /// Appends one encoded record to `out`. If a schema check fails, `out` is
+    /// left exactly as it was.
+    fn encode_record<I, T>(&mut self, record: I, out: &mut Vec<u8>) -> Result<(), CsvError>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let start = out.len();
+        let result = self.encode_fields(record, out);
+        if result.is_err() {
+            out.truncate(start);
+        }
+        self.record_index += 1;
+        result
+    }
+
+    /// Appends a header row, which is neither checked against the schema nor
+    /// counted as a record.
+    fn encode_header<I, T>(&mut self, names: I, out: &mut Vec<u8>) -> Result<(), CsvError>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let schema = self.schema.take();
+        let result = self.encode_fields(names, out);
+        self.schema = schema;
+        result
+    }
+
+    fn encode_fields<I, T>(&mut self, record: I, out: &mut Vec<u8>) -> Result<(), CsvError>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let mut utf8_buf = [0u8; 4];
+        let pending_quote_names = std::mem::take(&mut self.pending_quote_names);
+        let mut field_count = 0;
+
+        for (i, field) in record.into_iter().enumerate() {
+            if i > 0 {
+                out.extend_from_slice(self.config.delimiter.encode_utf8(&mut utf8_buf).as_bytes());
+            }
+            if pending_quote_names.iter().any(|name| name == field.as_ref()) {
+                self.force_quote(i);
+            }
+            let force_quote = self.forced_quotes.get(i).copied().unwrap_or(false);
+            let text = match self.formats.get(i) {
+                Some(Some(format)) => {
+                    // Formatting goes through a reused scratch buffer, never a fresh String.
+                    self.scratch.clear();
+                    format.apply(field.as_ref(), &mut self.scratch);
+                    self.scratch.as_str()
+                }
+                _ => field.as_ref(),
+            };
+            if let Some(schema) = &self.schema {
+                schema.check_field(self.record_index, i, text, self.config.null_tokens)?;
+            }
+            encode_field(text, &self.config, force_quote, out);
+            field_count += 1;
+        }
+
+        if let Some(schema) = &self.schema {
+            schema.check_field_count(self.record_index, field_count)?;
+        }
+        if self.config.terminator == Terminator::CrLf {
+            out.push(b'\r');
+        }
+        out.push(b'\n');
+        Ok(())
+    }
+}
+
+/// Writes records through a buffer. Buffered records are written when the
+/// writer is dropped, but errors there are lost: call `flush` (or
+/// `into_inner`) to see them.
+pub struct CsvWriter<W: std::io::Write> {
+    /// Always `Some`, until `into_inner` takes it.
+    inner: Option<W>,
+    encoder: RecordEncoder,
+    buffer: Vec<u8>,
+    /// Whether `serialize` writes a header row before its record: set with
+    /// `CsvConfig::has_headers` until anything is written.
+    header_pending: bool,
+}
+
+impl<W: std::io::Write> CsvWriter<W> {
+    pub fn new(inner: W, config: CsvConfig) -> Self {
+        CsvWriter {
+            inner: Some(inner),
+            encoder: RecordEncoder::new(config),
+            buffer: Vec::with_capacity(WRITER_FLUSH_THRESHOLD),
+            header_pending: config.has_headers,
+        }
+    }
+
+    /// Always quotes fields in column `index`, even when minimal quoting wouldn't.
+    pub fn with_quoted_column(mut self, index: usize) -> Self {
+        self.encoder.force_quote(index);
+        self
+    }
+
+    /// Always quotes the column whose header is `name`. The name is matched
+    /// against the first record written, which is expected to be the header row.
+    pub fn with_quoted_column_named(mut self, name: &str) -> Self {
+        self.encoder.pending_quote_names.push(name.to_string());
+        self
+    }
+
+    /// Validates every record written against `schema`; a violating record is
+    /// rejected with `CsvError::SchemaViolation` and nothing is written for it.
+    pub fn with_schema(mut self, schema: WriteSchema) -> Self {
+        self.encoder.schema = Some(schema);
+        self
+    }
+
+    /// Writes the attached schema's column names as a header row.
+    pub fn write_header(&mut self) -> Result<(), CsvError> {
+        let Some(schema) = &self.encoder.schema else {
+            return Ok(());
+        };
+        let names: Vec<String> = schema.columns.iter().map(|c| c.name.clone()).collect();
+        self.header_pending = false;
+        self.encoder.encode_header(names, &mut self.buffer)?;
+        self.drain_if_full()
+    }
+
+    /// Applies `format` to every field written in column `index`.
+    pub fn with_column_format(mut self, index: usize, format: ColumnFormat) -> Self {
+        if self.encoder.formats.len() <= index {
+            self.encoder.formats.resize(index + 1, None);
+        }
+        self.encoder.formats[index] = Some(format);
+        self
+    }
+
+    pub fn write_record<I, T>(&mut self, record: I) -> Result<(), CsvError>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        self.header_pending = false;
+        self.encoder.encode_record(record, &mut self.buffer)?;
+        self.drain_if_full()
+    }
+
+    /// Writes every record from `records`, then flushes the underlying writer once.
+    pub fn write_all<R, I, T>(&mut self, records: R) -> Result<(), CsvError>
+    where
+        R: IntoIterator<Item = I>,
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        self.header_pending = false;
+        for record in records {
+            self.encoder.encode_record(record, &mut self.buffer)?;
+            self.drain_if_full()?;
+        }
+        self.flush()
+    }
+
+    /// Like `write_all`, but encodes batches of records on the rayon thread
+    /// pool. Output order matches the input order.
+    #[cfg(feature = "parallel")]
+    pub fn par_write_all<R, T>(&mut self, records: &[R]) -> Result<(), CsvError>
+    where
+        R: AsRef<[T]> + Sync,
+        T: AsRef<str> + Sync,
+    {
+        use rayon::prelude::*;
+
+        self.header_pending = false;
+        // Resolve header-based options on the calling thread before the encoder is cloned.
+        let mut records = records;
+        if !self.encoder.pending_quote_names.is_empty()
+            && let Some((header, rest)) = records.split_first()
+        {
+            self.encoder.encode_record(header.as_ref(), &mut self.buffer)?;
+            records = rest;
+        }
+
+        let encoder = &self.encoder;
+        let encoded: Vec<Vec<u8>> = records
+            .par_chunks(PARALLEL_ENCODE_BATCH)
+            .enumerate()
+            .map(|(batch_index, batch)| {
+                let mut encoder = encoder.clone();
+                encoder.record_index += batch_index * PARALLEL_ENCODE_BATCH;
+                let mut out = Vec::with_capacity(batch.len() * 64);
+                for record in batch {
+                    encoder.encode_record(record.as_ref(), &mut out)?;
+                }
+                Ok(out)
+            })
+            .collect::<Result<_, CsvError>>()?;
+        self.encoder.record_index += records.len();
+
+        for block in encoded {
+            self.buffer.extend_from_slice(&block);
+            self.drain_if_full()?;
+        }
+        self.flush()
+    }
+
+    pub fn flush(&mut self) -> Result<(), CsvError> {
+        self.drain()?;
+        self.inner_mut().flush()?;
+        Ok(())
+    }
+
+    pub fn into_inner(mut self) -> Result<W, CsvError> {
+        self.flush()?;
+        Ok(self.inner.take().expect("inner is only taken here"))
+    }
+
+    #[inline]
+    fn inner_mut(&mut self) -> &mut W {
+        self.inner.as_mut().expect("inner is only taken by into_inner")
+    }
+
+    #[inline]
+    fn drain_if_full(&mut self) -> Result<(), CsvError> {
+        if self.buffer.len() >= WRITER_FLUSH_THRESHOLD {
+            self.drain()?;
+        }
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), CsvError> {
+        let inner = self.inner.as_mut().expect("inner is only taken by into_inner");
+        inner.write_all(&self.buffer)?;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+impl<W: std::io::Write> Drop for CsvWriter<W> {
+    /// Writes out buffered records, as `std::io::BufWriter` does, ignoring errors.
+    fn drop(&mut self) {
+        if self.inner.is_some() && !std::thread::panicking() {
+            let _ = self.flush();
+        }
+    }
+}
+
+/// Serializes in-memory rows to a CSV string using the writer's quoting rules.
+pub fn to_csv_string<R, T>(rows: &[R], config: &CsvConfig) -> String
+where
+    R: AsRef<[T]>,
+    T: AsRef<str>,
+{
+    let mut builder = CsvStringBuilder::new(*config);
+    for row in rows {
+        builder.push_record(row.as_ref());
+    }
+    builder.finish()
+}
+
+/// Accumulates records into an in-memory CSV string. Also collectable from an
+/// iterator of records, using the default config.
+pub struct CsvStringBuilder {
+    encoder: RecordEncoder,
+    buffer: Vec<u8>,
+}
+
+impl CsvStringBuilder {
+    pub fn new(config: CsvConfig) -> Self {
+        CsvStringBuilder {
+            encoder: RecordEncoder::new(config),
+            buffer: Vec::new(),
+        }
+    }
+
+    pub fn push_record<I, T>(&mut self, record: I) -> &mut Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        // Without a schema attached, encoding cannot fail.
+        let _ = self.encoder.encode_record(record, &mut self.buffer);
+        self
+    }
+
+    pub fn finish(self) -> String {
+        String::from_utf8(self.buffer).expect("records are encoded from valid UTF-8")
+    }
+}
+
+impl<I, T> Extend<I> for CsvStringBuilder
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+{
+    fn extend<R: IntoIterator<Item = I>>(&mut self, records: R) {
+        for record in records {
+            self.push_record(record);
+        }
+    }
+}
+
+impl<I, T> FromIterator<I> for CsvStringBuilder
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+{
+    fn from_iter<R: IntoIterator<Item = I>>(records: R) -> Self {
+        let mut builder = CsvStringBuilder::new(CsvConfig::default());
+        builder.extend(records);
+        builder
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<W: std::io::Write> CsvWriter<flate2::write::GzEncoder<W>> {
+    /// Writes gzip-compressed CSV (`.csv.gz`) to `inner`.
+    pub fn gzip(inner: W, config: CsvConfig) -> Self {
+        let encoder = flate2::write::GzEncoder::new(inner, flate2::Compression::default());
+        CsvWriter::new(encoder, config)
+    }
+
+    /// Flushes pending records and writes the gzip trailer.
+    pub fn finish(self) -> Result<W, CsvError> {
+        Ok(self.into_inner()?.finish()?)
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl<W: std::io::Write> CsvWriter<zstd::Encoder<'static, W>> {
+    /// Writes zstd-compressed CSV (`.csv.zst`) to `inner` at the given compression level.
+    pub fn zstd(inner: W, config: CsvConfig, level: i32) -> Result<Self, CsvError> {
+        let encoder = zstd::Encoder::new(inner, level)?;
+        Ok(CsvWriter::new(encoder, config))
+    }
+
+    /// Flushes pending records and ends the zstd frame.
+    pub fn finish(self) -> Result<W, CsvError> {
+        Ok(self.into_inner()?.finish()?)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<W: std::io::Write> CsvWriter<W> {
+    /// Writes `record` as one row: a struct's fields in declaration order, or
+    /// the elements of a tuple or sequence. A field holding a struct is
+    /// flattened into `<field>_<key>` columns, as `StringRecord::deserialize`
+    /// reads them back, and `None` is written as an empty field. With
+    /// `CsvConfig::has_headers`, a struct written before anything else also
+    /// writes the header row of its column names.
+    pub fn serialize<T: serde::Serialize + ?Sized>(&mut self, record: &T) -> Result<(), CsvError> {
+        let mut row = ser::SerializedRecord::default();
+        record.serialize(ser::RecordSerializer { record: &mut row }).map_err(|error| CsvError::Serialize {
+            record_index: self.encoder.record_index,
+            error,
+        })?;
+        if std::mem::take(&mut self.header_pending) && !row.names.is_empty() {
+            self.encoder.encode_header(&row.names, &mut self.buffer)?;
+        }
+        self.encoder.encode_record(&row.fields, &mut self.buffer)?;
+        self.drain_if_full()
+    }
+
+    /// Serializes every record from `records` with `serialize`, then flushes
+    /// the underlying writer once.
+    pub fn write_all_serialized<I>(&mut self, records: I) -> Result<(), CsvError>
+    where
+        I: IntoIterator,
+        I::Item: serde::Serialize,
+    {
+        for record in records {
+            self.serialize(&record)?;
+        }
+        self.flush()
+    }
+}
+
+/// Streams CSV from one dialect into another: input is parsed with one
+/// `CsvConfig` and re-serialized with another, one chunk at a time.
+#[derive(Debug, Clone, Copy)]
+pub struct CsvRewriter {
+    input: CsvConfig,
+    output: CsvConfig,
+    encoding: InputEncoding,
+    chunk_size: usize,
+}
+
+impl CsvRewriter {
+    pub fn new(input: CsvConfig, output: CsvConfig) -> Self {
+        CsvRewriter {
+            input,
+            output,
+            encoding: InputEncoding::default(),
+            chunk_size: DEFAULT_READ_CHUNK_SIZE,
+        }
+    }
+
+    pub fn with_input_encoding(mut self, encoding: InputEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Rewrites all of `reader` into `writer`, returning the number of data records
+    /// written. A header row captured via `has_headers` is passed through first.
+    pub fn rewrite<R: std::io::Read, W: std::io::Write>(&self, reader: R, writer: W) -> Result<usize, CsvError> {
+        let mut csv_reader = CsvReader::new(reader, self.input)
+            .with_input_encoding(self.encoding)
+            .with_chunk_size(self.chunk_size);
+        let mut csv_writer = CsvWriter::new(writer, self.output);
+        let mut records_written = 0;
+
+        if let Some(headers) = csv_reader.headers()? {
+            csv_writer.write_record(headers)?;
+        }
+        while let Some(record) = csv_reader.next_record()? {
+            csv_writer.write_record(&record)?;
+            records_written += 1;
+        }
+        csv_writer.flush()?;
+
+        Ok(records_written)
+    }
+}